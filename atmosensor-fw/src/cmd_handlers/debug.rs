@@ -0,0 +1,6 @@
+use crate::protocol::{DeviceMessage, PingResponse};
+use crate::tasks::send_usb_msg;
+
+pub fn ping() {
+    send_usb_msg(&DeviceMessage::PingResponse(PingResponse {}));
+}
@@ -0,0 +1,17 @@
+use core::sync::atomic::{AtomicU16, Ordering};
+
+use crate::cmd_handlers::response::acknowledge;
+use crate::protocol::{EventMask, SetEventMask};
+
+/// Kinds of unsolicited message the host wants, everything until it says otherwise
+static EVENT_MASK: AtomicU16 = AtomicU16::new(EventMask::all().bits());
+
+pub fn set_event_mask(msg: &SetEventMask) {
+    EVENT_MASK.store(msg.mask.bits(), Ordering::Relaxed);
+    acknowledge(SetEventMask::GROUP, SetEventMask::NUMBER, Ok(()));
+}
+
+/// Whether the host wants unsolicited messages of the kind `event`
+pub fn subscribed(event: EventMask) -> bool {
+    EventMask::from_bits_truncate(EVENT_MASK.load(Ordering::Relaxed)).contains(event)
+}
@@ -1,2 +1,6 @@
+pub mod debug;
+pub mod events;
 pub mod led;
+pub mod protocol_info;
+pub mod response;
 pub mod sensor;
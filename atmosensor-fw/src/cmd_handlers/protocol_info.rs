@@ -0,0 +1,16 @@
+use crate::protocol::{DeviceMessage, ProtocolInfoResponse, PROTOCOL_HASH, PROTOCOL_VERSION};
+use crate::tasks::send_usb_msg;
+
+/// Reports the protocol revision and which of `group`'s commands are in `supported_commands`
+pub fn get_protocol_info(group: u8, supported_commands: &[(u8, u8)]) {
+    let mut bitmap = [0u8; 32];
+    for (_, number) in supported_commands.iter().filter(|(grp, _)| *grp == group) {
+        bitmap[*number as usize / 8] |= 1 << (number % 8);
+    }
+    send_usb_msg(&DeviceMessage::ProtocolInfoResponse(ProtocolInfoResponse {
+        version: heapless::String::from(PROTOCOL_VERSION),
+        spec_hash: PROTOCOL_HASH,
+        group,
+        supported_commands: bitmap,
+    }));
+}
@@ -0,0 +1,21 @@
+use crate::protocol::{DeviceMessage, ErrorCode, ErrorResponse, GenericResponse};
+use crate::tasks::send_usb_msg;
+
+/// Reports to the host that command `number` of `group` failed
+pub fn send_error(group: u8, number: u8, code: ErrorCode) {
+    send_usb_msg(&DeviceMessage::ErrorResponse(ErrorResponse {
+        code,
+        failed_group: group,
+        failed_command: number,
+    }));
+}
+
+/// Answers an acknowledged command with the outcome of carrying it out
+pub fn acknowledge(group: u8, number: u8, result: Result<(), ErrorCode>) {
+    match result {
+        Ok(()) => send_usb_msg(&DeviceMessage::GenericResponse(GenericResponse {
+            successful: true,
+        })),
+        Err(code) => send_error(group, number, code),
+    }
+}
@@ -1,59 +1,104 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use stm32f1xx_hal::i2c;
+
+use crate::cmd_handlers::events::subscribed;
+use crate::cmd_handlers::response::{acknowledge, send_error};
 use crate::drivers;
+use crate::protocol::{
+    DeviceMessage, ErrorCode, EventMask, LastCO2DataResponse, LastHumidityResponse,
+    LastTemperatureResponse, MeasurementReport, ReportNewData, RequestLastCO2Data,
+    RequestLastHumidity, RequestLastTemperature, RequestMeasurementReport, SetAltitude,
+    SetMeasurementInterval, SetMeasurementPush, SetTemperatureOffset, StartContinuousMeasurement,
+};
 use crate::static_resources::with_i2c_bus;
 use crate::tasks::send_usb_msg;
-use crate::tasks::Command;
-use crate::tasks::{SensorCommand, UtilityCommand};
+use crate::utils::uptime_ms;
 
-static mut LAST_CO2_READING: Option<u16> = None;
-static mut LAST_TEMPERATURE_READING: Option<f32> = None;
-static mut LAST_HUMIDITY_READING: Option<f32> = None;
+#[derive(Clone, Copy)]
+struct Sample {
+    measurement: drivers::Measurement,
+    /// Counts the samples read so far, starting at 1
+    number: u32,
+    read_at_ms: u32,
+}
 
-pub fn set_measurement_interval(interval_s: u16) {
-    let is_successful = unsafe {
-        with_i2c_bus(|i2c| {
-            let mut scd_sensor = drivers::Scd30::new(i2c);
-            scd_sensor.set_measurement_interval(interval_s).is_ok()
-        })
-    };
-    send_usb_msg(&Command::Utility(UtilityCommand::GenericResponse {
-        Successful: is_successful,
-    }));
+static mut LAST_SAMPLE: Option<Sample> = None;
+/// Whether new samples are pushed as [MeasurementReport] rather than announced with
+/// [ReportNewData]
+static PUSH_MEASUREMENTS: AtomicBool = AtomicBool::new(false);
+
+/// Maps a failed transfer with the SCD30 to the code reported to the host
+fn sensor_error(err: i2c::Error) -> ErrorCode {
+    match err {
+        i2c::Error::Acknowledge => ErrorCode::SensorNack,
+        i2c::Error::Timeout => ErrorCode::SensorTimeout,
+        _ => ErrorCode::SensorBusError,
+    }
 }
 
-pub fn set_altitude(altitude_m: u16) {
-    let is_successful = unsafe {
-        with_i2c_bus(|i2c| {
-            let mut scd_sensor = drivers::Scd30::new(i2c);
-            scd_sensor.set_altitude(altitude_m).is_ok()
-        })
-    };
-    send_usb_msg(&Command::Utility(UtilityCommand::GenericResponse {
-        Successful: is_successful,
-    }));
+pub fn set_measurement_interval(msg: &SetMeasurementInterval) {
+    let result = msg
+        .check_ranges()
+        .map_err(|_| ErrorCode::InvalidArgument)
+        .and_then(|_| unsafe {
+            with_i2c_bus(|i2c| {
+                let mut scd_sensor = drivers::Scd30::new(i2c);
+                scd_sensor
+                    .set_measurement_interval(msg.measurement_interval)
+                    .map_err(sensor_error)
+            })
+        });
+    acknowledge(
+        SetMeasurementInterval::GROUP,
+        SetMeasurementInterval::NUMBER,
+        result,
+    );
 }
 
-pub fn set_temperature_offset(temp_offset: u16) {
-    let is_successful = unsafe {
-        with_i2c_bus(|i2c| {
-            let mut scd_sensor = drivers::Scd30::new(i2c);
-            scd_sensor.set_temperature_offset(temp_offset).is_ok()
-        })
-    };
-    send_usb_msg(&Command::Utility(UtilityCommand::GenericResponse {
-        Successful: is_successful,
-    }));
+pub fn set_altitude(msg: &SetAltitude) {
+    let result = msg
+        .check_ranges()
+        .map_err(|_| ErrorCode::InvalidArgument)
+        .and_then(|_| unsafe {
+            with_i2c_bus(|i2c| {
+                let mut scd_sensor = drivers::Scd30::new(i2c);
+                scd_sensor.set_altitude(msg.altitude).map_err(sensor_error)
+            })
+        });
+    acknowledge(SetAltitude::GROUP, SetAltitude::NUMBER, result);
+}
+
+pub fn set_temperature_offset(msg: &SetTemperatureOffset) {
+    let result = msg
+        .check_ranges()
+        .map_err(|_| ErrorCode::InvalidArgument)
+        .and_then(|_| unsafe {
+            with_i2c_bus(|i2c| {
+                let mut scd_sensor = drivers::Scd30::new(i2c);
+                scd_sensor
+                    .set_temperature_offset(msg.temperature_offset)
+                    .map_err(sensor_error)
+            })
+        });
+    acknowledge(
+        SetTemperatureOffset::GROUP,
+        SetTemperatureOffset::NUMBER,
+        result,
+    );
 }
 
 pub fn start_continuous_measurement() {
-    let is_successful = unsafe {
+    let result = unsafe {
         with_i2c_bus(|i2c| {
             let mut scd_sensor = drivers::Scd30::new(i2c);
-            scd_sensor.start_measuring().is_ok()
+            scd_sensor.start_measuring().map_err(sensor_error)
         })
     };
-    send_usb_msg(&Command::Utility(UtilityCommand::GenericResponse {
-        Successful: is_successful,
-    }));
+    acknowledge(
+        StartContinuousMeasurement::GROUP,
+        StartContinuousMeasurement::NUMBER,
+        result,
+    );
 
     unsafe {
         with_i2c_bus(|i2c| {
@@ -63,49 +108,117 @@ pub fn start_continuous_measurement() {
     }
 }
 
+pub fn set_measurement_push(msg: &SetMeasurementPush) {
+    PUSH_MEASUREMENTS.store(msg.enabled, Ordering::Relaxed);
+    acknowledge(
+        SetMeasurementPush::GROUP,
+        SetMeasurementPush::NUMBER,
+        Ok(()),
+    );
+}
+
 pub fn handle_data_ready() {
-    unsafe {
+    let new_sample = unsafe {
         with_i2c_bus(|i2c| {
             let mut scd_sensor = drivers::Scd30::new(i2c);
-            if let Ok(true) = scd_sensor.data_ready() {
-                if let Ok(Some(measurement)) = scd_sensor.read() {
-                    LAST_CO2_READING = Some(measurement.co2 as u16);
-                }
+            match scd_sensor.data_ready() {
+                Ok(true) => scd_sensor.read(),
+                Ok(false) => Ok(None),
+                Err(err) => Err(err),
             }
         })
-    };
-    send_usb_msg(&Command::Sensor(SensorCommand::ReportNewData));
+    }
+    .unwrap_or_else(|err| {
+        warn!("Failed to read a sample: {:?}", err);
+        None
+    });
+    if let Some(measurement) = new_sample {
+        let number = unsafe { LAST_SAMPLE }.map_or(1, |sample| sample.number.wrapping_add(1));
+        unsafe {
+            LAST_SAMPLE = Some(Sample {
+                measurement,
+                number,
+                read_at_ms: uptime_ms(),
+            });
+        }
+    }
+
+    if !subscribed(EventMask::NEW_DATA) {
+        return;
+    }
+    if !PUSH_MEASUREMENTS.load(Ordering::Relaxed) {
+        send_usb_msg(&DeviceMessage::ReportNewData(ReportNewData {}));
+    } else if new_sample.is_some() {
+        if let Some(report) = unsafe { LAST_SAMPLE }.map(measurement_report) {
+            send_usb_msg(&DeviceMessage::MeasurementReport(report));
+        }
+    }
+}
+
+fn measurement_report(sample: Sample) -> MeasurementReport {
+    MeasurementReport {
+        sample_number: sample.number,
+        sample_age: uptime_ms().wrapping_sub(sample.read_at_ms),
+        co_2: sample.measurement.co2,
+        temperature: sample.measurement.temperature,
+        relative_humidity: sample.measurement.humidity,
+    }
+}
+
+pub fn handle_request_measurement_report() {
+    match unsafe { LAST_SAMPLE } {
+        Some(sample) => send_usb_msg(&DeviceMessage::MeasurementReport(measurement_report(
+            sample,
+        ))),
+        None => send_error(
+            RequestMeasurementReport::GROUP,
+            RequestMeasurementReport::NUMBER,
+            ErrorCode::NoData,
+        ),
+    }
+}
+
+/// Builds a response from one value of the last sample, or the code explaining why there is
+/// none
+fn last_reading<T>(
+    reading: impl FnOnce(drivers::Measurement) -> f32,
+    response: impl FnOnce(f32) -> Result<T, ()>,
+) -> Result<T, ErrorCode> {
+    let sample = unsafe { LAST_SAMPLE }.ok_or(ErrorCode::NoData)?;
+    response(reading(sample.measurement)).map_err(|_| ErrorCode::ValueOutOfRange)
 }
 
 pub fn handle_request_co2_data() {
-    let msg = if let Some(last_co2_data) = unsafe { LAST_CO2_READING } {
-        Command::Sensor(SensorCommand::LastCO2DataResponse {
-            CO2Data: last_co2_data,
-        })
-    } else {
-        Command::Utility(UtilityCommand::GenericResponse { Successful: false })
-    };
-    send_usb_msg(&msg);
+    match last_reading(|measurement| measurement.co2, LastCO2DataResponse::new) {
+        Ok(response) => send_usb_msg(&DeviceMessage::LastCO2DataResponse(response)),
+        Err(code) => send_error(RequestLastCO2Data::GROUP, RequestLastCO2Data::NUMBER, code),
+    }
 }
 
 pub fn handle_request_temperature() {
-    let msg = if let Some(last_temperature_data) = unsafe { LAST_TEMPERATURE_READING } {
-        Command::Sensor(SensorCommand::LastTemperatureResponse {
-            Temperature: last_temperature_data as i16,
-        })
-    } else {
-        Command::Utility(UtilityCommand::GenericResponse { Successful: false })
-    };
-    send_usb_msg(&msg);
+    match last_reading(
+        |measurement| measurement.temperature,
+        LastTemperatureResponse::new,
+    ) {
+        Ok(response) => send_usb_msg(&DeviceMessage::LastTemperatureResponse(response)),
+        Err(code) => send_error(
+            RequestLastTemperature::GROUP,
+            RequestLastTemperature::NUMBER,
+            code,
+        ),
+    }
 }
 
 pub fn handle_request_humidity() {
-    let msg = if let Some(last_humidity_data) = unsafe { LAST_HUMIDITY_READING } {
-        Command::Sensor(SensorCommand::LastHumidityResponse {
-            RelativeHumidity: last_humidity_data as u16,
-        })
-    } else {
-        Command::Utility(UtilityCommand::GenericResponse { Successful: false })
-    };
-    send_usb_msg(&msg);
+    match last_reading(
+        |measurement| measurement.humidity,
+        LastHumidityResponse::new,
+    ) {
+        Ok(response) => send_usb_msg(&DeviceMessage::LastHumidityResponse(response)),
+        Err(code) => send_error(
+            RequestLastHumidity::GROUP,
+            RequestLastHumidity::NUMBER,
+            code,
+        ),
+    }
 }
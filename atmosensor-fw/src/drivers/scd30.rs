@@ -26,7 +26,7 @@ pub struct Scd30<'a, T: ?Sized> {
     address: u8,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Measurement {
     pub co2: f32,
     pub humidity: f32,
@@ -0,0 +1,65 @@
+//! Log output which is sent to the host as [LogMessage]s, so it can be read without a debugger
+//! attached. Use the macros like `format!`: `warn!("dropped {} frames", count)`.
+
+use core::fmt::{self, Write};
+
+use crate::cmd_handlers::events::subscribed;
+use crate::protocol::{DeviceMessage, EventMask, LogLevel, LogMessage};
+use crate::tasks::send_usb_msg;
+
+/// Collects formatted text, dropping whatever doesn't fit instead of failing
+struct Truncating<const N: usize>(heapless::String<N>);
+
+impl<const N: usize> Write for Truncating<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.0.push(c).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn send_log(level: LogLevel, args: fmt::Arguments) {
+    let event = match level {
+        LogLevel::Error | LogLevel::Warn => EventMask::ERRORS,
+        LogLevel::Info | LogLevel::Debug => EventMask::LOG_LINES,
+    };
+    if !subscribed(event) {
+        return;
+    }
+
+    let mut text = Truncating(heapless::String::new());
+    let _ = text.write_fmt(args);
+    send_usb_msg(&DeviceMessage::LogMessage(LogMessage {
+        level,
+        text: text.0,
+    }));
+}
+
+macro_rules! log {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::log::send_log($crate::protocol::LogLevel::$level, format_args!($($arg)+))
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! error {
+    ($($arg:tt)+) => { log!(Error, $($arg)+) };
+}
+
+#[allow(unused_macros)]
+macro_rules! warn {
+    ($($arg:tt)+) => { log!(Warn, $($arg)+) };
+}
+
+#[allow(unused_macros)]
+macro_rules! info {
+    ($($arg:tt)+) => { log!(Info, $($arg)+) };
+}
+
+#[allow(unused_macros)]
+macro_rules! debug {
+    ($($arg:tt)+) => { log!(Debug, $($arg)+) };
+}
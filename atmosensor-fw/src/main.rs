@@ -2,7 +2,7 @@
 #![no_main]
 
 use cortex_m::asm::delay;
-use cortex_m_rt::entry;
+use cortex_m_rt::{entry, exception};
 use panic_semihosting as _;
 use stm32f1xx_hal::gpio::Edge;
 use stm32f1xx_hal::gpio::ExtiPin;
@@ -11,19 +11,23 @@ use stm32f1xx_hal::pac::{interrupt, Interrupt, NVIC};
 use stm32f1xx_hal::prelude::*;
 use stm32f1xx_hal::{rcc::RccExt, usb::Peripheral};
 
+#[macro_use]
+mod log;
+
 mod cmd_handlers;
 mod drivers;
+mod protocol;
 mod static_resources;
 mod tasks;
 mod utils;
 
 use static_resources::*;
-use tasks::push_new_cmd;
-use tasks::{Command, SensorCommand};
+use tasks::notify_data_ready;
 
 #[entry]
 fn main() -> ! {
     let mut device_peripherals = stm32f1xx_hal::pac::Peripherals::take().unwrap();
+    let core_peripherals = cortex_m::Peripherals::take().unwrap();
     let mut flash = device_peripherals.FLASH.constrain();
     let rcc = device_peripherals.RCC.constrain();
     // Set with the Clock Configuration tab of STM32CubeMX
@@ -36,6 +40,7 @@ fn main() -> ! {
         .pclk2(48.MHz())
         .freeze(&mut flash.acr);
     assert!(clocks.usbclk_valid());
+    utils::start_clock(core_peripherals.SYST, clocks.sysclk().raw());
 
     let mut gpioa = device_peripherals.GPIOA.split();
     let mut gpiob = device_peripherals.GPIOB.split();
@@ -88,7 +93,7 @@ fn main() -> ! {
         NVIC::unmask(Interrupt::EXTI0);
     }
 
-    let command_handler = tasks::CommandHandler::new();
+    let mut command_handler = tasks::CommandHandler::new();
 
     loop {
         usb_handler.run();
@@ -101,7 +106,12 @@ fn main() -> ! {
 fn EXTI0() {
     let data_rdy_pin = unsafe { &mut *SCD_DATA_RDY_PIN.as_mut_ptr() };
     if data_rdy_pin.check_interrupt() {
-        push_new_cmd(&Command::Sensor(SensorCommand::ReportNewData));
+        notify_data_ready();
         data_rdy_pin.clear_interrupt_pending_bit();
     }
 }
+
+#[exception]
+fn SysTick() {
+    utils::tick();
+}
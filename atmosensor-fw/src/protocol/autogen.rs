@@ -0,0 +1,1451 @@
+#![allow(dead_code, unused_mut)]
+
+/// Version of `protocol.json5` which this module was generated from
+pub const PROTOCOL_VERSION: &str = "0.5.3";
+/// Hash of `protocol.json5`, equal between peers generated from the same definition
+pub const PROTOCOL_HASH: u32 = 0xe6ce09ae;
+/// Numbers of the protocol's groups
+pub const PROTOCOL_GROUPS: [u8; 3] = [1, 222, 170];
+
+/// A message which the host sends
+#[derive(Clone, Debug)]
+pub enum HostMessage {
+    SetMeasurementInterval(SetMeasurementInterval),
+    SetAltitude(SetAltitude),
+    SetTemperatureOffset(SetTemperatureOffset),
+    StartContinuousMeasurement(StartContinuousMeasurement),
+    RequestLastCO2Data(RequestLastCO2Data),
+    RequestLastTemperature(RequestLastTemperature),
+    RequestLastHumidity(RequestLastHumidity),
+    RequestMeasurementReport(RequestMeasurementReport),
+    SetMeasurementPush(SetMeasurementPush),
+    Ping(Ping),
+    EnableTestLed(EnableTestLed),
+    DisableTestLed(DisableTestLed),
+    GetProtocolInfo(GetProtocolInfo),
+    SetEventMask(SetEventMask),
+    /// A message which isn't described by this revision of the protocol, e.g. one from a newer
+    /// peer. Only its header is kept.
+    Unknown {
+        group: u8,
+        number: u8,
+    },
+}
+
+impl HostMessage {
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        if buf.len() < 2 {
+            return None;
+        }
+        match (buf[0], buf[1]) {
+            (1, 0) => Some(HostMessage::SetMeasurementInterval(
+                SetMeasurementInterval::decode(&buf[2..])?,
+            )),
+            (1, 1) => Some(HostMessage::SetAltitude(SetAltitude::decode(&buf[2..])?)),
+            (1, 2) => Some(HostMessage::SetTemperatureOffset(
+                SetTemperatureOffset::decode(&buf[2..])?,
+            )),
+            (1, 3) => Some(HostMessage::StartContinuousMeasurement(
+                StartContinuousMeasurement::decode(&buf[2..])?,
+            )),
+            (1, 5) => Some(HostMessage::RequestLastCO2Data(RequestLastCO2Data::decode(
+                &buf[2..],
+            )?)),
+            (1, 7) => Some(HostMessage::RequestLastTemperature(
+                RequestLastTemperature::decode(&buf[2..])?,
+            )),
+            (1, 9) => Some(HostMessage::RequestLastHumidity(
+                RequestLastHumidity::decode(&buf[2..])?,
+            )),
+            (1, 11) => Some(HostMessage::RequestMeasurementReport(
+                RequestMeasurementReport::decode(&buf[2..])?,
+            )),
+            (1, 13) => Some(HostMessage::SetMeasurementPush(SetMeasurementPush::decode(
+                &buf[2..],
+            )?)),
+            (222, 0) => Some(HostMessage::Ping(Ping::decode(&buf[2..])?)),
+            (170, 0) => Some(HostMessage::EnableTestLed(EnableTestLed::decode(
+                &buf[2..],
+            )?)),
+            (170, 1) => Some(HostMessage::DisableTestLed(DisableTestLed::decode(
+                &buf[2..],
+            )?)),
+            (170, 3) => Some(HostMessage::GetProtocolInfo(GetProtocolInfo::decode(
+                &buf[2..],
+            )?)),
+            (170, 6) => Some(HostMessage::SetEventMask(SetEventMask::decode(&buf[2..])?)),
+            (group, number) => Some(HostMessage::Unknown { group, number }),
+        }
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        match self {
+            HostMessage::SetMeasurementInterval(inner) => inner.encode_into(buf),
+            HostMessage::SetAltitude(inner) => inner.encode_into(buf),
+            HostMessage::SetTemperatureOffset(inner) => inner.encode_into(buf),
+            HostMessage::StartContinuousMeasurement(inner) => inner.encode_into(buf),
+            HostMessage::RequestLastCO2Data(inner) => inner.encode_into(buf),
+            HostMessage::RequestLastTemperature(inner) => inner.encode_into(buf),
+            HostMessage::RequestLastHumidity(inner) => inner.encode_into(buf),
+            HostMessage::RequestMeasurementReport(inner) => inner.encode_into(buf),
+            HostMessage::SetMeasurementPush(inner) => inner.encode_into(buf),
+            HostMessage::Ping(inner) => inner.encode_into(buf),
+            HostMessage::EnableTestLed(inner) => inner.encode_into(buf),
+            HostMessage::DisableTestLed(inner) => inner.encode_into(buf),
+            HostMessage::GetProtocolInfo(inner) => inner.encode_into(buf),
+            HostMessage::SetEventMask(inner) => inner.encode_into(buf),
+            // The payload wasn't kept, so the message can't be sent on
+            HostMessage::Unknown { .. } => Err(()),
+        }
+    }
+}
+
+/// A message which the device sends
+#[derive(Clone, Debug)]
+pub enum DeviceMessage {
+    ReportNewData(ReportNewData),
+    LastCO2DataResponse(LastCO2DataResponse),
+    LastTemperatureResponse(LastTemperatureResponse),
+    LastHumidityResponse(LastHumidityResponse),
+    MeasurementReport(MeasurementReport),
+    PingResponse(PingResponse),
+    LogMessage(LogMessage),
+    GenericResponse(GenericResponse),
+    ProtocolInfoResponse(ProtocolInfoResponse),
+    ErrorResponse(ErrorResponse),
+    /// A message which isn't described by this revision of the protocol, e.g. one from a newer
+    /// peer. Only its header is kept.
+    Unknown {
+        group: u8,
+        number: u8,
+    },
+}
+
+impl DeviceMessage {
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        if buf.len() < 2 {
+            return None;
+        }
+        match (buf[0], buf[1]) {
+            (1, 4) => Some(DeviceMessage::ReportNewData(ReportNewData::decode(
+                &buf[2..],
+            )?)),
+            (1, 6) => Some(DeviceMessage::LastCO2DataResponse(
+                LastCO2DataResponse::decode(&buf[2..])?,
+            )),
+            (1, 8) => Some(DeviceMessage::LastTemperatureResponse(
+                LastTemperatureResponse::decode(&buf[2..])?,
+            )),
+            (1, 10) => Some(DeviceMessage::LastHumidityResponse(
+                LastHumidityResponse::decode(&buf[2..])?,
+            )),
+            (1, 12) => Some(DeviceMessage::MeasurementReport(MeasurementReport::decode(
+                &buf[2..],
+            )?)),
+            (222, 1) => Some(DeviceMessage::PingResponse(PingResponse::decode(
+                &buf[2..],
+            )?)),
+            (222, 2) => Some(DeviceMessage::LogMessage(LogMessage::decode(&buf[2..])?)),
+            (170, 2) => Some(DeviceMessage::GenericResponse(GenericResponse::decode(
+                &buf[2..],
+            )?)),
+            (170, 4) => Some(DeviceMessage::ProtocolInfoResponse(
+                ProtocolInfoResponse::decode(&buf[2..])?,
+            )),
+            (170, 5) => Some(DeviceMessage::ErrorResponse(ErrorResponse::decode(
+                &buf[2..],
+            )?)),
+            (group, number) => Some(DeviceMessage::Unknown { group, number }),
+        }
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        match self {
+            DeviceMessage::ReportNewData(inner) => inner.encode_into(buf),
+            DeviceMessage::LastCO2DataResponse(inner) => inner.encode_into(buf),
+            DeviceMessage::LastTemperatureResponse(inner) => inner.encode_into(buf),
+            DeviceMessage::LastHumidityResponse(inner) => inner.encode_into(buf),
+            DeviceMessage::MeasurementReport(inner) => inner.encode_into(buf),
+            DeviceMessage::PingResponse(inner) => inner.encode_into(buf),
+            DeviceMessage::LogMessage(inner) => inner.encode_into(buf),
+            DeviceMessage::GenericResponse(inner) => inner.encode_into(buf),
+            DeviceMessage::ProtocolInfoResponse(inner) => inner.encode_into(buf),
+            DeviceMessage::ErrorResponse(inner) => inner.encode_into(buf),
+            // The payload wasn't kept, so the message can't be sent on
+            DeviceMessage::Unknown { .. } => Err(()),
+        }
+    }
+}
+
+/// Handles the messages which the host sends, with one method per message. Every method
+/// answers with [Handler::not_supported] unless it's implemented, as do messages which this
+/// revision of the protocol doesn't describe.
+pub trait Handler {
+    /// Answers the host's message `(group, number)`, which this handler doesn't support
+    fn not_supported(&mut self, group: u8, number: u8);
+
+    fn set_measurement_interval(&mut self, _msg: SetMeasurementInterval) {
+        self.not_supported(1, 0);
+    }
+
+    fn set_altitude(&mut self, _msg: SetAltitude) {
+        self.not_supported(1, 1);
+    }
+
+    fn set_temperature_offset(&mut self, _msg: SetTemperatureOffset) {
+        self.not_supported(1, 2);
+    }
+
+    fn start_continuous_measurement(&mut self, _msg: StartContinuousMeasurement) {
+        self.not_supported(1, 3);
+    }
+
+    fn request_last_co_2_data(&mut self, _msg: RequestLastCO2Data) {
+        self.not_supported(1, 5);
+    }
+
+    fn request_last_temperature(&mut self, _msg: RequestLastTemperature) {
+        self.not_supported(1, 7);
+    }
+
+    fn request_last_humidity(&mut self, _msg: RequestLastHumidity) {
+        self.not_supported(1, 9);
+    }
+
+    fn request_measurement_report(&mut self, _msg: RequestMeasurementReport) {
+        self.not_supported(1, 11);
+    }
+
+    fn set_measurement_push(&mut self, _msg: SetMeasurementPush) {
+        self.not_supported(1, 13);
+    }
+
+    fn ping(&mut self, _msg: Ping) {
+        self.not_supported(222, 0);
+    }
+
+    fn enable_test_led(&mut self, _msg: EnableTestLed) {
+        self.not_supported(170, 0);
+    }
+
+    fn disable_test_led(&mut self, _msg: DisableTestLed) {
+        self.not_supported(170, 1);
+    }
+
+    fn get_protocol_info(&mut self, _msg: GetProtocolInfo) {
+        self.not_supported(170, 3);
+    }
+
+    fn set_event_mask(&mut self, _msg: SetEventMask) {
+        self.not_supported(170, 6);
+    }
+}
+
+/// Passes `msg` to the matching method of `handler`
+pub fn dispatch(handler: &mut impl Handler, msg: HostMessage) {
+    match msg {
+        HostMessage::SetMeasurementInterval(inner) => handler.set_measurement_interval(inner),
+        HostMessage::SetAltitude(inner) => handler.set_altitude(inner),
+        HostMessage::SetTemperatureOffset(inner) => handler.set_temperature_offset(inner),
+        HostMessage::StartContinuousMeasurement(inner) => {
+            handler.start_continuous_measurement(inner)
+        }
+        HostMessage::RequestLastCO2Data(inner) => handler.request_last_co_2_data(inner),
+        HostMessage::RequestLastTemperature(inner) => handler.request_last_temperature(inner),
+        HostMessage::RequestLastHumidity(inner) => handler.request_last_humidity(inner),
+        HostMessage::RequestMeasurementReport(inner) => handler.request_measurement_report(inner),
+        HostMessage::SetMeasurementPush(inner) => handler.set_measurement_push(inner),
+        HostMessage::Ping(inner) => handler.ping(inner),
+        HostMessage::EnableTestLed(inner) => handler.enable_test_led(inner),
+        HostMessage::DisableTestLed(inner) => handler.disable_test_led(inner),
+        HostMessage::GetProtocolInfo(inner) => handler.get_protocol_info(inner),
+        HostMessage::SetEventMask(inner) => handler.set_event_mask(inner),
+        HostMessage::Unknown { group, number } => handler.not_supported(group, number),
+    }
+}
+
+/// Sequence id of frames which the device sends on its own rather than in reply to a frame from
+/// the host. The host numbers its frames from 1 and skips this id when wrapping around.
+pub const UNSOLICITED_SEQUENCE: u8 = 0;
+
+/// CRC-16/CCITT-FALSE of a frame's sequence id and message, which follows them big-endian
+/// before the frame is COBS-encoded
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Checks the CRC at the end of a decoded frame, returning the sequence id and message in front
+/// of it if the CRC matches
+pub fn strip_crc(frame: &[u8]) -> Option<&[u8]> {
+    let (message, crc) = frame.split_at(frame.len().checked_sub(2)?);
+    (crc16(message).to_be_bytes() == crc).then_some(message)
+}
+
+struct WireReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.buf.len() < N {
+            return None;
+        }
+        let (head, tail) = self.buf.split_at(N);
+        let mut out = [0u8; N];
+        out.copy_from_slice(head);
+        self.buf = tail;
+        Some(out)
+    }
+
+    fn take_slice(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.buf.len() < len {
+            return None;
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Some(head)
+    }
+
+    /// Fails if bytes are left over after the last parameter
+    fn finish(self) -> Option<()> {
+        self.buf.is_empty().then_some(())
+    }
+}
+
+struct WireWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> WireWriter<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    fn put(&mut self, data: &[u8]) -> Result<(), ()> {
+        let end = self.len + data.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(())?
+            .copy_from_slice(data);
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Rounds a physical value which was divided by its scale to the nearest raw value, `None` if
+/// that doesn't fit in `T`
+fn to_raw<T: TryFrom<i64>>(value: f32) -> Option<T> {
+    if !value.is_finite() {
+        return None;
+    }
+    let rounded = if value < 0.0 {
+        value - 0.5
+    } else {
+        value + 0.5
+    };
+    T::try_from(rounded as i64).ok()
+}
+
+/// Why the device failed to carry out a command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ErrorCode {
+    /// The firmware doesn't implement the command
+    UnsupportedCommand = 0,
+    /// A parameter is outside of the range the protocol allows
+    InvalidArgument = 1,
+    /// The sensor hasn't taken a measurement yet
+    NoData = 2,
+    /// The measurement can't be represented in the response
+    ValueOutOfRange = 3,
+    /// The sensor didn't acknowledge an I2C transfer
+    SensorNack = 4,
+    /// An I2C transfer with the sensor timed out
+    SensorTimeout = 5,
+    /// The I2C bus failed, e.g. by losing arbitration or overrunning
+    SensorBusError = 6,
+}
+
+impl TryFrom<u8> for ErrorCode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        match value {
+            0 => Ok(ErrorCode::UnsupportedCommand),
+            1 => Ok(ErrorCode::InvalidArgument),
+            2 => Ok(ErrorCode::NoData),
+            3 => Ok(ErrorCode::ValueOutOfRange),
+            4 => Ok(ErrorCode::SensorNack),
+            5 => Ok(ErrorCode::SensorTimeout),
+            6 => Ok(ErrorCode::SensorBusError),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<ErrorCode> for u8 {
+    fn from(value: ErrorCode) -> Self {
+        value as u8
+    }
+}
+
+/// Severity of a log message from the firmware
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LogLevel {
+    /// Something failed
+    Error = 0,
+    /// Something unexpected happened which the firmware recovered from
+    Warn = 1,
+    /// Normal operation
+    Info = 2,
+    /// Details which help with debugging
+    Debug = 3,
+}
+
+impl TryFrom<u8> for LogLevel {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        match value {
+            0 => Ok(LogLevel::Error),
+            1 => Ok(LogLevel::Warn),
+            2 => Ok(LogLevel::Info),
+            3 => Ok(LogLevel::Debug),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<LogLevel> for u8 {
+    fn from(value: LogLevel) -> Self {
+        value as u8
+    }
+}
+
+/// Kinds of message which the device sends on its own
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventMask(u16);
+
+impl EventMask {
+    /// ReportNewData, or MeasurementReport while SetMeasurementPush is enabled
+    pub const NEW_DATA: Self = Self(1);
+    /// LogMessage at the Error and Warn levels
+    pub const ERRORS: Self = Self(2);
+    /// LogMessage at the Info and Debug levels
+    pub const LOG_LINES: Self = Self(4);
+    /// Notifications when a value crosses a threshold, which the firmware doesn't send yet
+    pub const THRESHOLD_CROSSINGS: Self = Self(8);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn all() -> Self {
+        Self(1 | 2 | 4 | 8)
+    }
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Builds a set from raw bits, dropping any which don't correspond to a known flag
+    pub const fn from_bits_truncate(bits: u16) -> Self {
+        Self(bits & Self::all().0)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl core::ops::BitOr for EventMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitOrAssign for EventMask {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl core::ops::BitAnd for EventMask {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl TryFrom<u16> for EventMask {
+    type Error = u16;
+
+    fn try_from(bits: u16) -> Result<Self, u16> {
+        if bits & !Self::all().0 == 0 {
+            Ok(Self(bits))
+        } else {
+            Err(bits)
+        }
+    }
+}
+
+impl From<EventMask> for u16 {
+    fn from(value: EventMask) -> Self {
+        value.0
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetMeasurementInterval {
+    pub measurement_interval: u16,
+}
+
+impl SetMeasurementInterval {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 0;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let measurement_interval = u16::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self {
+            measurement_interval,
+        })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.measurement_interval.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(measurement_interval_seconds: f32) -> Result<Self, ()> {
+        let message = Self {
+            measurement_interval: to_raw(measurement_interval_seconds).ok_or(())?,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        if !(2..=1800).contains(&self.measurement_interval) {
+            return Err(());
+        }
+        Ok(())
+    }
+
+    /// MeasurementInterval in seconds
+    pub fn measurement_interval_seconds(&self) -> f32 {
+        self.measurement_interval as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetAltitude {
+    pub altitude: u16,
+}
+
+impl SetAltitude {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 1;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let altitude = u16::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self { altitude })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.altitude.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(altitude_meters: f32) -> Result<Self, ()> {
+        let message = Self {
+            altitude: to_raw(altitude_meters).ok_or(())?,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// Altitude in meters
+    pub fn altitude_meters(&self) -> f32 {
+        self.altitude as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetTemperatureOffset {
+    pub temperature_offset: u16,
+}
+
+impl SetTemperatureOffset {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 2;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let temperature_offset = u16::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self { temperature_offset })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.temperature_offset.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(temperature_offset_celsius: f32) -> Result<Self, ()> {
+        let message = Self {
+            temperature_offset: to_raw(temperature_offset_celsius / 0.01).ok_or(())?,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// TemperatureOffset in celsius
+    pub fn temperature_offset_celsius(&self) -> f32 {
+        self.temperature_offset as f32 * 0.01
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StartContinuousMeasurement {}
+
+impl StartContinuousMeasurement {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 3;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReportNewData {}
+
+impl ReportNewData {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 4;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestLastCO2Data {}
+
+impl RequestLastCO2Data {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 5;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LastCO2DataResponse {
+    pub co_2_data: u16,
+}
+
+impl LastCO2DataResponse {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 6;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let co_2_data = u16::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self { co_2_data })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.co_2_data.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(co_2_data_ppm: f32) -> Result<Self, ()> {
+        let message = Self {
+            co_2_data: to_raw(co_2_data_ppm).ok_or(())?,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// CO2Data in ppm
+    pub fn co_2_data_ppm(&self) -> f32 {
+        self.co_2_data as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestLastTemperature {}
+
+impl RequestLastTemperature {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 7;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LastTemperatureResponse {
+    pub temperature: i16,
+}
+
+impl LastTemperatureResponse {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 8;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let temperature = i16::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self { temperature })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.temperature.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(temperature_celsius: f32) -> Result<Self, ()> {
+        let message = Self {
+            temperature: to_raw(temperature_celsius / 0.01).ok_or(())?,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// Temperature in celsius
+    pub fn temperature_celsius(&self) -> f32 {
+        self.temperature as f32 * 0.01
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestLastHumidity {}
+
+impl RequestLastHumidity {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 9;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LastHumidityResponse {
+    pub relative_humidity: u16,
+}
+
+impl LastHumidityResponse {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 10;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let relative_humidity = u16::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self { relative_humidity })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.relative_humidity.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(relative_humidity_percent: f32) -> Result<Self, ()> {
+        let message = Self {
+            relative_humidity: to_raw(relative_humidity_percent / 0.1).ok_or(())?,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        if !(0..=1000).contains(&self.relative_humidity) {
+            return Err(());
+        }
+        Ok(())
+    }
+
+    /// RelativeHumidity in percent
+    pub fn relative_humidity_percent(&self) -> f32 {
+        self.relative_humidity as f32 * 0.1
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestMeasurementReport {}
+
+impl RequestMeasurementReport {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 11;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MeasurementReport {
+    pub sample_number: u32,
+    pub sample_age: u32,
+    pub co_2: f32,
+    pub temperature: f32,
+    pub relative_humidity: f32,
+}
+
+impl MeasurementReport {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 12;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let sample_number = u32::from_be_bytes(reader.take()?);
+
+        let sample_age = u32::from_be_bytes(reader.take()?);
+
+        let co_2 = f32::from_be_bytes(reader.take()?);
+
+        let temperature = f32::from_be_bytes(reader.take()?);
+
+        let relative_humidity = f32::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self {
+            sample_number,
+            sample_age,
+            co_2,
+            temperature,
+            relative_humidity,
+        })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.check_ranges()?;
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.sample_number.to_be_bytes())?;
+
+        writer.put(&self.sample_age.to_be_bytes())?;
+
+        writer.put(&self.co_2.to_be_bytes())?;
+
+        writer.put(&self.temperature.to_be_bytes())?;
+
+        writer.put(&self.relative_humidity.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+
+    /// Builds the message from values in their physical units, failing if one is outside of
+    /// the range the protocol allows for it
+    pub fn new(
+        sample_number: u32,
+        sample_age_milliseconds: f32,
+        co_2_ppm: f32,
+        temperature_celsius: f32,
+        relative_humidity_percent: f32,
+    ) -> Result<Self, ()> {
+        let message = Self {
+            sample_number,
+            sample_age: to_raw(sample_age_milliseconds).ok_or(())?,
+            co_2: co_2_ppm,
+            temperature: temperature_celsius,
+            relative_humidity: relative_humidity_percent,
+        };
+        message.check_ranges()?;
+        Ok(message)
+    }
+
+    /// Checks that every parameter is within the range the protocol allows for it, which
+    /// decoding doesn't
+    pub fn check_ranges(&self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// SampleAge in milliseconds
+    pub fn sample_age_milliseconds(&self) -> f32 {
+        self.sample_age as f32
+    }
+
+    /// CO2 in ppm
+    pub fn co_2_ppm(&self) -> f32 {
+        self.co_2
+    }
+
+    /// Temperature in celsius
+    pub fn temperature_celsius(&self) -> f32 {
+        self.temperature
+    }
+
+    /// RelativeHumidity in percent
+    pub fn relative_humidity_percent(&self) -> f32 {
+        self.relative_humidity
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetMeasurementPush {
+    pub enabled: bool,
+}
+
+impl SetMeasurementPush {
+    pub const GROUP: u8 = 1;
+    pub const NUMBER: u8 = 13;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let enabled = reader.take::<1>()?[0] != 0;
+
+        reader.finish()?;
+
+        Some(Self { enabled })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&[self.enabled as u8])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Ping {}
+
+impl Ping {
+    pub const GROUP: u8 = 222;
+    pub const NUMBER: u8 = 0;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PingResponse {}
+
+impl PingResponse {
+    pub const GROUP: u8 = 222;
+    pub const NUMBER: u8 = 1;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogMessage {
+    pub level: LogLevel,
+    pub text: heapless::String<64>,
+}
+
+impl LogMessage {
+    pub const GROUP: u8 = 222;
+    pub const NUMBER: u8 = 2;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let level = LogLevel::try_from(u8::from_be_bytes(reader.take()?)).ok()?;
+
+        let text = {
+            let len = reader.take::<1>()?[0] as usize;
+
+            let mut text = heapless::String::new();
+            text.push_str(core::str::from_utf8(reader.take_slice(len)?).ok()?)
+                .ok()?;
+            text
+        };
+        reader.finish()?;
+
+        Some(Self { level, text })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&u8::from(self.level).to_be_bytes())?;
+
+        writer.put(&[self.text.len() as u8])?;
+        writer.put(self.text.as_bytes())?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EnableTestLed {}
+
+impl EnableTestLed {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 0;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DisableTestLed {}
+
+impl DisableTestLed {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 1;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        reader.finish()?;
+
+        Some(Self {})
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericResponse {
+    pub successful: bool,
+}
+
+impl GenericResponse {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 2;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let successful = reader.take::<1>()?[0] != 0;
+
+        reader.finish()?;
+
+        Some(Self { successful })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&[self.successful as u8])?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GetProtocolInfo {
+    pub group: u8,
+}
+
+impl GetProtocolInfo {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 3;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let group = u8::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self { group })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&self.group.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtocolInfoResponse {
+    pub version: heapless::String<16>,
+    pub spec_hash: u32,
+    pub group: u8,
+    pub supported_commands: [u8; 32],
+}
+
+impl ProtocolInfoResponse {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 4;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let version = {
+            let len = reader.take::<1>()?[0] as usize;
+
+            let mut text = heapless::String::new();
+            text.push_str(core::str::from_utf8(reader.take_slice(len)?).ok()?)
+                .ok()?;
+            text
+        };
+        let spec_hash = u32::from_be_bytes(reader.take()?);
+
+        let group = u8::from_be_bytes(reader.take()?);
+
+        let supported_commands = reader.take()?;
+
+        reader.finish()?;
+
+        Some(Self {
+            version,
+            spec_hash,
+            group,
+            supported_commands,
+        })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&[self.version.len() as u8])?;
+        writer.put(self.version.as_bytes())?;
+
+        writer.put(&self.spec_hash.to_be_bytes())?;
+
+        writer.put(&self.group.to_be_bytes())?;
+
+        writer.put(&self.supported_commands)?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub failed_group: u8,
+    pub failed_command: u8,
+}
+
+impl ErrorResponse {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 5;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let code = ErrorCode::try_from(u8::from_be_bytes(reader.take()?)).ok()?;
+
+        let failed_group = u8::from_be_bytes(reader.take()?);
+
+        let failed_command = u8::from_be_bytes(reader.take()?);
+
+        reader.finish()?;
+
+        Some(Self {
+            code,
+            failed_group,
+            failed_command,
+        })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&u8::from(self.code).to_be_bytes())?;
+
+        writer.put(&self.failed_group.to_be_bytes())?;
+
+        writer.put(&self.failed_command.to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetEventMask {
+    pub mask: EventMask,
+}
+
+impl SetEventMask {
+    pub const GROUP: u8 = 170;
+    pub const NUMBER: u8 = 6;
+
+    #[allow(unused)]
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = WireReader::new(buf);
+
+        let mask = EventMask::try_from(u16::from_be_bytes(reader.take()?)).ok()?;
+
+        reader.finish()?;
+
+        Some(Self { mask })
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, ()> {
+        let mut writer = WireWriter::new(buf);
+        writer.put(&[Self::GROUP, Self::NUMBER])?;
+
+        writer.put(&u16::from(self.mask).to_be_bytes())?;
+
+        Ok(writer.len)
+    }
+}
@@ -0,0 +1,29 @@
+use super::{crc16, DeviceMessage};
+
+/// Encodes `msg` into `out` as the frame with id `sequence`: the sequence id, the message and
+/// their CRC, COBS-encoded and terminated by 0x00. `buffer` holds the frame before it's
+/// encoded. Returns the number of bytes written to `out`.
+pub fn encode_frame(
+    sequence: u8,
+    msg: &DeviceMessage,
+    buffer: &mut [u8],
+    out: &mut [u8],
+) -> Result<usize, ()> {
+    if buffer.len() < 3 {
+        return Err(());
+    }
+    // Leave room for the sequence id in front of the message and the CRC behind it
+    buffer[0] = sequence;
+    let message_end = buffer.len() - 2;
+    let frame_bytes = msg.encode_into(&mut buffer[1..message_end])? + 1;
+    let crc = crc16(&buffer[..frame_bytes]);
+    buffer[frame_bytes..frame_bytes + 2].copy_from_slice(&crc.to_be_bytes());
+
+    let frame = &buffer[..frame_bytes + 2];
+    if out.len() <= cobs::max_encoding_length(frame.len()) {
+        return Err(());
+    }
+    let encoded_bytes = cobs::encode(frame, out);
+    out[encoded_bytes] = 0x00;
+    Ok(encoded_bytes + 1)
+}
@@ -0,0 +1,4 @@
+mod autogen;
+mod frame;
+pub use autogen::*;
+pub use frame::*;
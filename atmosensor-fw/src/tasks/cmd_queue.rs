@@ -1,22 +1,40 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
 use crate::cmd_handlers;
-use crate::tasks::{Command, SensorCommand, UtilityCommand};
+use crate::cmd_handlers::response::send_error;
+use crate::protocol::{
+    self, DisableTestLed, EnableTestLed, ErrorCode, GetProtocolInfo, Handler, HostMessage, Ping,
+    RequestLastCO2Data, RequestLastHumidity, RequestLastTemperature, RequestMeasurementReport,
+    SetAltitude, SetEventMask, SetMeasurementInterval, SetMeasurementPush, SetTemperatureOffset,
+    StartContinuousMeasurement,
+};
+use crate::tasks::set_reply_sequence;
+
+/// Received commands along with the sequence id of the frame they arrived in
+static mut CMD_QUEUE: CommandQueue<(u8, HostMessage), 48> = CommandQueue::new();
+/// Set from the sensor's data ready interrupt, handled by the next [CommandHandler::run]
+static DATA_READY: AtomicBool = AtomicBool::new(false);
 
-static mut CMD_QUEUE: CommandQueue<48> = CommandQueue::new();
+pub fn push_new_cmd(sequence: u8, cmd: &HostMessage) {
+    let _ = critical_section::with(|_cs| unsafe { CMD_QUEUE.push((sequence, cmd.clone())) });
+}
 
-pub fn push_new_cmd(cmd: &Command) {
-    let _ = critical_section::with(|_cs| unsafe { CMD_QUEUE.push(*cmd) });
+pub fn notify_data_ready() {
+    DATA_READY.store(true, Ordering::Release);
 }
 
-pub struct CommandQueue<const N: usize> {
-    elements: [Command; N],
+pub struct CommandQueue<T, const N: usize> {
+    elements: [Option<T>; N],
     write: usize,
     read: usize,
 }
 
-impl<const N: usize> CommandQueue<N> {
+impl<T, const N: usize> CommandQueue<T, N> {
+    const EMPTY: Option<T> = None;
+
     pub const fn new() -> Self {
         Self {
-            elements: [Command::Nop; N],
+            elements: [Self::EMPTY; N],
             write: 0,
             read: 0,
         }
@@ -34,11 +52,11 @@ impl<const N: usize> CommandQueue<N> {
         }
     }
 
-    pub fn push(&mut self, cmd: Command) -> Result<(), ()> {
+    pub fn push(&mut self, cmd: T) -> Result<(), ()> {
         if self.full() {
             Err(())
         } else {
-            self.elements[self.write] = cmd;
+            self.elements[self.write] = Some(cmd);
             if self.write == N - 1 {
                 self.write = 0;
             } else {
@@ -48,7 +66,7 @@ impl<const N: usize> CommandQueue<N> {
         }
     }
 
-    pub fn pop(&mut self) -> Option<Command> {
+    pub fn pop(&mut self) -> Option<T> {
         if self.write != self.read {
             let pop_idx = self.read;
             if self.read == N - 1 {
@@ -56,13 +74,43 @@ impl<const N: usize> CommandQueue<N> {
             } else {
                 self.read += 1;
             }
-            Some(self.elements[pop_idx].clone())
+            self.elements[pop_idx].take()
         } else {
             None
         }
     }
 }
 
+/// `(group, number)` of every command which [CommandHandler] implements
+const SUPPORTED_COMMANDS: [(u8, u8); 14] = [
+    (
+        SetMeasurementInterval::GROUP,
+        SetMeasurementInterval::NUMBER,
+    ),
+    (SetAltitude::GROUP, SetAltitude::NUMBER),
+    (SetTemperatureOffset::GROUP, SetTemperatureOffset::NUMBER),
+    (
+        StartContinuousMeasurement::GROUP,
+        StartContinuousMeasurement::NUMBER,
+    ),
+    (RequestLastCO2Data::GROUP, RequestLastCO2Data::NUMBER),
+    (
+        RequestLastTemperature::GROUP,
+        RequestLastTemperature::NUMBER,
+    ),
+    (RequestLastHumidity::GROUP, RequestLastHumidity::NUMBER),
+    (
+        RequestMeasurementReport::GROUP,
+        RequestMeasurementReport::NUMBER,
+    ),
+    (SetMeasurementPush::GROUP, SetMeasurementPush::NUMBER),
+    (Ping::GROUP, Ping::NUMBER),
+    (EnableTestLed::GROUP, EnableTestLed::NUMBER),
+    (DisableTestLed::GROUP, DisableTestLed::NUMBER),
+    (GetProtocolInfo::GROUP, GetProtocolInfo::NUMBER),
+    (SetEventMask::GROUP, SetEventMask::NUMBER),
+];
+
 pub struct CommandHandler {}
 
 impl CommandHandler {
@@ -70,45 +118,79 @@ impl CommandHandler {
         CommandHandler {}
     }
 
-    pub fn run(&self) {
+    pub fn run(&mut self) {
+        if DATA_READY.swap(false, Ordering::Acquire) {
+            cmd_handlers::sensor::handle_data_ready();
+        }
+
         let cmd = critical_section::with(|_cs| unsafe { CMD_QUEUE.pop() });
-        if let Some(cmd) = cmd {
-            match cmd {
-                Command::Nop => {}
-                Command::Utility(UtilityCommand::EnableTestLed) => {
-                    cmd_handlers::led::enable_test_led();
-                }
-                Command::Utility(UtilityCommand::DisableTestLed) => {
-                    cmd_handlers::led::disable_test_led();
-                }
-                Command::Sensor(SensorCommand::SetMeasurementInterval {
-                    MeasurementInterval,
-                }) => {
-                    cmd_handlers::sensor::set_measurement_interval(MeasurementInterval);
-                }
-                Command::Sensor(SensorCommand::SetAltitude { Altitude }) => {
-                    cmd_handlers::sensor::set_altitude(Altitude);
-                }
-                Command::Sensor(SensorCommand::SetTemperatureOffset { TemperatureOffset }) => {
-                    cmd_handlers::sensor::set_temperature_offset(TemperatureOffset);
-                }
-                Command::Sensor(SensorCommand::StartContinuousMeasurement) => {
-                    cmd_handlers::sensor::start_continuous_measurement();
-                }
-                Command::Sensor(SensorCommand::ReportNewData) => {
-                    cmd_handlers::sensor::handle_data_ready();
-                }
-                Command::Sensor(SensorCommand::RequestLastCO2Data) => {
-                    cmd_handlers::sensor::handle_request_co2_data();
-                }
-                Command::Sensor(SensorCommand::RequestLastTemperature) => {
-                    cmd_handlers::sensor::handle_request_temperature();
-                }
-                Command::Sensor(SensorCommand::RequestLastHumidity) => {
-                    cmd_handlers::sensor::handle_request_humidity();
-                }
-                _ => {}
-            }
+        if let Some((sequence, cmd)) = cmd {
+            // Everything sent while handling the command answers it
+            set_reply_sequence(sequence);
+            protocol::dispatch(self, cmd);
+            set_reply_sequence(protocol::UNSOLICITED_SEQUENCE);
         }
     }
 }
+
+impl Handler for CommandHandler {
+    fn not_supported(&mut self, group: u8, number: u8) {
+        send_error(group, number, ErrorCode::UnsupportedCommand);
+    }
+
+    fn set_measurement_interval(&mut self, msg: SetMeasurementInterval) {
+        cmd_handlers::sensor::set_measurement_interval(&msg);
+    }
+
+    fn set_altitude(&mut self, msg: SetAltitude) {
+        cmd_handlers::sensor::set_altitude(&msg);
+    }
+
+    fn set_temperature_offset(&mut self, msg: SetTemperatureOffset) {
+        cmd_handlers::sensor::set_temperature_offset(&msg);
+    }
+
+    fn start_continuous_measurement(&mut self, _msg: StartContinuousMeasurement) {
+        cmd_handlers::sensor::start_continuous_measurement();
+    }
+
+    fn request_last_co_2_data(&mut self, _msg: RequestLastCO2Data) {
+        cmd_handlers::sensor::handle_request_co2_data();
+    }
+
+    fn request_last_temperature(&mut self, _msg: RequestLastTemperature) {
+        cmd_handlers::sensor::handle_request_temperature();
+    }
+
+    fn request_last_humidity(&mut self, _msg: RequestLastHumidity) {
+        cmd_handlers::sensor::handle_request_humidity();
+    }
+
+    fn request_measurement_report(&mut self, _msg: RequestMeasurementReport) {
+        cmd_handlers::sensor::handle_request_measurement_report();
+    }
+
+    fn set_measurement_push(&mut self, msg: SetMeasurementPush) {
+        cmd_handlers::sensor::set_measurement_push(&msg);
+    }
+
+    fn ping(&mut self, _msg: Ping) {
+        cmd_handlers::debug::ping();
+    }
+
+    fn enable_test_led(&mut self, _msg: EnableTestLed) {
+        cmd_handlers::led::enable_test_led();
+    }
+
+    fn disable_test_led(&mut self, _msg: DisableTestLed) {
+        cmd_handlers::led::disable_test_led();
+    }
+
+    fn get_protocol_info(&mut self, msg: GetProtocolInfo) {
+        cmd_handlers::protocol_info::get_protocol_info(msg.group, &SUPPORTED_COMMANDS);
+    }
+
+    fn set_event_mask(&mut self, msg: SetEventMask) {
+        cmd_handlers::events::set_event_mask(&msg);
+    }
+}
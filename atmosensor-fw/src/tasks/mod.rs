@@ -3,6 +3,3 @@ pub use usb_handler::*;
 
 mod cmd_queue;
 pub use cmd_queue::*;
-
-pub mod commands;
-pub use commands::*;
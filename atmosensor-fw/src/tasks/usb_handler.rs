@@ -1,4 +1,5 @@
 use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
 use stm32f1xx_hal::pac::{interrupt, Interrupt, NVIC};
 use stm32f1xx_hal::{
     usb::Peripheral,
@@ -7,7 +8,8 @@ use stm32f1xx_hal::{
 use usb_device::{bus::UsbBusAllocator, prelude::*};
 use usbd_serial::{SerialPort, USB_CLASS_CDC};
 
-use crate::tasks::{push_new_cmd, Command, CommandQueue};
+use crate::protocol::{self, DeviceMessage, HostMessage};
+use crate::tasks::{push_new_cmd, CommandQueue};
 use crate::utils::CobsBuffer;
 
 static mut USB_BUS: Option<UsbBusAllocator<UsbBusType>> = None;
@@ -20,15 +22,29 @@ static mut USB_RX_BUFFER: MaybeUninit<CobsBuffer<1024>> = MaybeUninit::uninit();
 static mut USB_RAW_BUFFER: [u8; 1024] = [0u8; 1024];
 static mut NOT_ENCODED_YET_BUFFER: [u8; 256] = [0u8; 256];
 
-static mut USB_RESPONSE_QUEUE: CommandQueue<12> = CommandQueue::new();
+/// Frames dropped because their CRC didn't match
+static CORRUPT_FRAMES: AtomicU32 = AtomicU32::new(0);
 
-pub fn send_usb_msg(cmd: &Command) {
+/// Sequence id which messages sent right now are tagged with
+static REPLY_SEQUENCE: AtomicU8 = AtomicU8::new(protocol::UNSOLICITED_SEQUENCE);
+
+/// Outbound messages along with the sequence id of their frame
+static mut USB_RESPONSE_QUEUE: CommandQueue<(u8, DeviceMessage), 12> = CommandQueue::new();
+
+/// Tags messages sent from now on as replies to the host's frame with id `sequence`, or as
+/// unsolicited with [protocol::UNSOLICITED_SEQUENCE]
+pub fn set_reply_sequence(sequence: u8) {
+    REPLY_SEQUENCE.store(sequence, Ordering::Relaxed);
+}
+
+pub fn send_usb_msg(cmd: &DeviceMessage) {
+    let sequence = REPLY_SEQUENCE.load(Ordering::Relaxed);
     critical_section::with(|_cs| {
-        let _ = unsafe { USB_RESPONSE_QUEUE.push(*cmd) };
+        let _ = unsafe { USB_RESPONSE_QUEUE.push((sequence, cmd.clone())) };
     })
 }
 
-fn get_next_outbound_usb_msg() -> Option<Command> {
+fn get_next_outbound_usb_msg() -> Option<(u8, DeviceMessage)> {
     critical_section::with(|_cs| unsafe { USB_RESPONSE_QUEUE.pop() })
 }
 
@@ -70,36 +86,32 @@ impl UsbHandler {
             return;
         }
 
-        let mut encoded_buf = [0u8; 4];
-        let serial = unsafe { USB_SERIAL.as_mut().unwrap() };
         let packet = critical_section::with(|cs| rx_buffer.read_packet(&cs, &mut cmd_buf));
 
-        match packet {
-            Ok(cmd_bytes) => {
-                if cmd_bytes >= 2 {
-                    if cmd_buf[0] == 0xde && cmd_buf[1] == 0x00 {
-                        let encoded_bytes = cobs::encode(&[0xde, 0x01], &mut encoded_buf);
-                        let _ = serial.write(&encoded_buf[..encoded_bytes]);
-                        let _ = serial.flush();
-                    } else if let Some(cmd) = crate::tasks::Command::from_bytes(&cmd_buf[..]) {
-                        push_new_cmd(&cmd);
+        if let Ok(frame_bytes) = packet {
+            match protocol::strip_crc(&cmd_buf[..frame_bytes]) {
+                Some([sequence, message @ ..]) => {
+                    // Messages this firmware doesn't know are queued too, the handler answers
+                    // them with UnsupportedCommand
+                    if let Some(cmd) = HostMessage::decode(message) {
+                        push_new_cmd(*sequence, &cmd);
                     }
                 }
+                Some([]) | None => {
+                    let dropped = CORRUPT_FRAMES.fetch_add(1, Ordering::Relaxed) + 1;
+                    warn!("Dropped a corrupt frame, {} so far", dropped);
+                }
             }
-            _ => {}
         }
 
         let usb_msg = get_next_outbound_usb_msg();
         let tx_buffer = unsafe { &mut USB_TX_RAW_BUFFER };
         let serial = unsafe { USB_SERIAL.as_mut().unwrap() };
-        if let Some(cmd) = usb_msg {
-            unsafe {
-                if let Ok(cmd_bytes) = cmd.to_bytes(&mut NOT_ENCODED_YET_BUFFER) {
-                    let encoded_bytes =
-                        cobs::encode(&NOT_ENCODED_YET_BUFFER[..cmd_bytes], tx_buffer);
-                    let _ = serial.write(&tx_buffer[..encoded_bytes]);
-                    let _ = serial.flush();
-                }
+        if let Some((sequence, cmd)) = usb_msg {
+            let unencoded = unsafe { &mut NOT_ENCODED_YET_BUFFER };
+            if let Ok(frame_bytes) = protocol::encode_frame(sequence, &cmd, unencoded, tx_buffer) {
+                let _ = serial.write(&tx_buffer[..frame_bytes]);
+                let _ = serial.flush();
             }
         }
     }
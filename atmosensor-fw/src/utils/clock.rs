@@ -0,0 +1,21 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Milliseconds since [start_clock], wrapping after about 49 days
+static UPTIME_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Makes SysTick fire every millisecond, given the core clock frequency
+pub fn start_clock(mut syst: cortex_m::peripheral::SYST, sysclk_hz: u32) {
+    syst.set_clock_source(cortex_m::peripheral::syst::SystClkSource::Core);
+    syst.set_reload(sysclk_hz / 1000 - 1);
+    syst.clear_current();
+    syst.enable_counter();
+    syst.enable_interrupt();
+}
+
+pub fn tick() {
+    UPTIME_MS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn uptime_ms() -> u32 {
+    UPTIME_MS.load(Ordering::Relaxed)
+}
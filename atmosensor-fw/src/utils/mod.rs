@@ -1,2 +1,5 @@
 mod cobs_buffer;
 pub use cobs_buffer::*;
+
+mod clock;
+pub use clock::*;
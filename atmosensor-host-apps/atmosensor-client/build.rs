@@ -0,0 +1,24 @@
+use std::path::{Path, PathBuf};
+
+const PROTOCOL_FILE: &str = "../../usb-protocol/protocol.json5";
+
+fn main() {
+    println!("cargo:rerun-if-changed={PROTOCOL_FILE}");
+
+    let protocol = match atmosensor_codegen::load_protocol(Path::new(PROTOCOL_FILE)) {
+        Ok(protocol) => protocol,
+        Err(atmosensor_codegen::Error::Invalid(problems)) => {
+            for problem in &problems {
+                println!("cargo:warning={PROTOCOL_FILE}: {problem}");
+            }
+            panic!("found {} problem(s) in the protocol", problems.len());
+        }
+        Err(err) => panic!("failed to load {PROTOCOL_FILE}: {err}"),
+    };
+
+    // Left unformatted, rustfmt isn't needed for a file which is only ever included
+    let module = atmosensor_codegen::generate_module(&protocol, atmosensor_codegen::Target::Client)
+        .expect("failed to generate the protocol module");
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("protocol.rs"), module).unwrap();
+}
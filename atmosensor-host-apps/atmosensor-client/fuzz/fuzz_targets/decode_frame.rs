@@ -0,0 +1,14 @@
+//! Feeds arbitrary bytes to the COBS decoding and CRC check which `Reader` runs on everything it
+//! reads from the serial port
+
+#![no_main]
+
+use atmosensor_client::protocol::DeviceMessage;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut buffer = vec![0; data.len()];
+    if let Ok((_, message)) = atmosensor_client::decode_frame(data, &mut buffer) {
+        let _ = DeviceMessage::from_bytes(message);
+    }
+});
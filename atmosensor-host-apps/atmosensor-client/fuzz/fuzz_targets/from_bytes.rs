@@ -0,0 +1,15 @@
+//! Decodes arbitrary bytes as a message in both directions, and encodes whatever decodes again
+
+#![no_main]
+
+use atmosensor_client::protocol::{DeviceMessage, HostMessage};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(msg) = HostMessage::from_bytes(data) {
+        let _ = msg.to_bytes();
+    }
+    if let Ok(msg) = DeviceMessage::from_bytes(data) {
+        let _ = msg.to_bytes();
+    }
+});
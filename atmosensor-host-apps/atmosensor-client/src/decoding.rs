@@ -0,0 +1,93 @@
+//! Checks that each kind of malformed payload is rejected with the matching [DecodeError], and
+//! that messages this revision of the protocol doesn't know are kept whole. The firmware's
+//! decoders are checked the same way in atmosensor-fw-tests.
+
+use crate::protocol::{
+    DecodeError, DeviceMessage, HostMessage, LogMessage, PingResponse, SetAltitude,
+};
+
+#[test]
+fn missing_header() {
+    assert_eq!(
+        HostMessage::from_bytes(&[]),
+        Err(DecodeError::MissingHeader)
+    );
+    assert_eq!(
+        DeviceMessage::from_bytes(&[LogMessage::GROUP]),
+        Err(DecodeError::MissingHeader)
+    );
+}
+
+#[test]
+fn truncated_payload() {
+    let bytes = [SetAltitude::GROUP, SetAltitude::NUMBER, 0x01];
+    assert_eq!(
+        HostMessage::from_bytes(&bytes),
+        Err(DecodeError::Truncated("SetAltitude"))
+    );
+}
+
+#[test]
+fn trailing_bytes() {
+    let bytes = [
+        SetAltitude::GROUP,
+        SetAltitude::NUMBER,
+        0x01,
+        0xa4,
+        0x00,
+        0x00,
+    ];
+    assert_eq!(
+        HostMessage::from_bytes(&bytes),
+        Err(DecodeError::TrailingBytes("SetAltitude", 2))
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let bytes = [
+        LogMessage::GROUP,
+        LogMessage::NUMBER,
+        0x02,
+        0x02,
+        0xc3,
+        0x28,
+    ];
+    assert_eq!(
+        DeviceMessage::from_bytes(&bytes),
+        Err(DecodeError::InvalidUtf8("LogMessage"))
+    );
+}
+
+#[test]
+fn invalid_enum_value() {
+    let bytes = [LogMessage::GROUP, LogMessage::NUMBER, 0x09, 0x00];
+    assert_eq!(
+        DeviceMessage::from_bytes(&bytes),
+        Err(DecodeError::InvalidValue("LogMessage"))
+    );
+}
+
+#[test]
+fn wrong_direction() {
+    let bytes = [PingResponse::GROUP, PingResponse::NUMBER];
+    assert_eq!(
+        HostMessage::from_bytes(&bytes),
+        Err(DecodeError::WrongDirection("PingResponse"))
+    );
+}
+
+#[test]
+fn unknown_messages_keep_their_payload() {
+    let bytes = [0x42, 0x07, 0x01, 0x02, 0x03];
+    let msg = DeviceMessage::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        msg,
+        DeviceMessage::Unknown {
+            group: 0x42,
+            number: 0x07,
+            payload: vec![0x01, 0x02, 0x03],
+        }
+    );
+    assert_eq!(msg.to_bytes().unwrap(), bytes);
+}
@@ -0,0 +1,75 @@
+//! Pins the text which the `display` feature writes for messages, as atmosensord logs it.
+
+use crate::protocol::{
+    DeviceMessage, ErrorCode, ErrorResponse, EventMask, HostMessage, LogLevel, LogMessage,
+    MeasurementReport, PingResponse, ProtocolInfoResponse, SetEventMask,
+};
+
+#[test]
+fn messages_without_parameters() {
+    assert_eq!(
+        DeviceMessage::from(PingResponse {}).to_string(),
+        "PingResponse"
+    );
+}
+
+#[test]
+fn values_have_their_units() {
+    let report = MeasurementReport {
+        sample_number: 7,
+        sample_age: 1500,
+        co_2: 612.5,
+        temperature: 21.25,
+        relative_humidity: 40.0,
+    };
+    assert_eq!(
+        DeviceMessage::from(report).to_string(),
+        "MeasurementReport { sample_number: 7, sample_age: 1500 milliseconds, co_2: 612.5 ppm, \
+         temperature: 21.25 celsius, relative_humidity: 40 percent }"
+    );
+}
+
+#[test]
+fn enums_have_their_names_and_bitflags_their_bits() {
+    let error = ErrorResponse {
+        code: ErrorCode::SensorTimeout,
+        failed_group: 1,
+        failed_command: 2,
+    };
+    assert_eq!(
+        DeviceMessage::from(error).to_string(),
+        "ErrorResponse { code: SensorTimeout, failed_group: 1, failed_command: 2 }"
+    );
+    let log = LogMessage {
+        level: LogLevel::Warn,
+        text: "sensor reset".to_owned(),
+    };
+    assert_eq!(
+        DeviceMessage::from(log).to_string(),
+        "LogMessage { level: Warn, text: \"sensor reset\" }"
+    );
+    let mask = SetEventMask {
+        mask: EventMask::NEW_DATA | EventMask::ERRORS,
+    };
+    assert_eq!(
+        HostMessage::from(mask).to_string(),
+        "SetEventMask { mask: EventMask(3) }"
+    );
+}
+
+#[test]
+fn byte_arrays_are_hex() {
+    let mut supported_commands = [0; 32];
+    supported_commands[0] = 0b101;
+    let info = ProtocolInfoResponse {
+        version: "1.2.0".to_owned(),
+        spec_hash: 0xdeadbeef,
+        group: 1,
+        supported_commands,
+    };
+    assert_eq!(
+        DeviceMessage::from(info).to_string(),
+        "ProtocolInfoResponse { version: \"1.2.0\", spec_hash: 3735928559, group: 1, \
+         supported_commands: 0500000000000000000000000000000000000000000000000000000000000000 }"
+    );
+}
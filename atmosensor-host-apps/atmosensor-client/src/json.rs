@@ -0,0 +1,117 @@
+//! Pins the JSON form of messages which the `serde` feature provides, which tools outside of
+//! this crate read and write: messages are tagged with their name, byte arrays are lists of
+//! numbers, enums are their value's name and bitflags are carried as their raw bits.
+
+use crate::protocol::{
+    DeviceMessage, ErrorCode, ErrorResponse, EventMask, HostMessage, LogLevel, LogMessage,
+    ProtocolInfoResponse, SetEventMask, StartContinuousMeasurement,
+};
+use proptest::prelude::*;
+use serde_json::json;
+
+fn protocol_info() -> DeviceMessage {
+    let mut supported_commands = [0; 32];
+    supported_commands[0] = 0b101;
+    ProtocolInfoResponse {
+        version: "1.2.0".to_owned(),
+        spec_hash: 0xdeadbeef,
+        group: 1,
+        supported_commands,
+    }
+    .into()
+}
+
+/// Asserts that `msg` serializes to `expected` and deserializes from it again
+fn assert_json<T>(msg: T, expected: serde_json::Value)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    assert_eq!(serde_json::to_value(&msg).unwrap(), expected);
+    assert_eq!(serde_json::from_value::<T>(expected).unwrap(), msg);
+}
+
+#[test]
+fn messages_are_tagged_with_their_name() {
+    assert_json(
+        HostMessage::StartContinuousMeasurement(StartContinuousMeasurement {}),
+        json!({ "message": "StartContinuousMeasurement" }),
+    );
+    assert_json(
+        DeviceMessage::LogMessage(LogMessage {
+            level: LogLevel::Warn,
+            text: "sensor reset".to_owned(),
+        }),
+        json!({ "message": "LogMessage", "level": "Warn", "text": "sensor reset" }),
+    );
+}
+
+#[test]
+fn byte_arrays_are_lists_of_numbers() {
+    let mut supported_commands = vec![0; 32];
+    supported_commands[0] = 0b101;
+    assert_json(
+        protocol_info(),
+        json!({
+            "message": "ProtocolInfoResponse",
+            "version": "1.2.0",
+            "spec_hash": 0xdeadbeef_u32,
+            "group": 1,
+            "supported_commands": supported_commands,
+        }),
+    );
+}
+
+#[test]
+fn enums_are_names_and_bitflags_are_bits() {
+    assert_json(
+        HostMessage::SetEventMask(SetEventMask {
+            mask: EventMask::NEW_DATA | EventMask::ERRORS,
+        }),
+        json!({ "message": "SetEventMask", "mask": 3 }),
+    );
+    assert_json(
+        DeviceMessage::ErrorResponse(ErrorResponse {
+            code: ErrorCode::SensorTimeout,
+            failed_group: 1,
+            failed_command: 2,
+        }),
+        json!({
+            "message": "ErrorResponse",
+            "code": "SensorTimeout",
+            "failed_group": 1,
+            "failed_command": 2,
+        }),
+    );
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let unknown_value = json!({ "message": "LogMessage", "level": "Fatal", "text": "" });
+    assert!(serde_json::from_value::<DeviceMessage>(unknown_value).is_err());
+    let unknown_bits = json!({ "message": "SetEventMask", "mask": 0x100 });
+    assert!(serde_json::from_value::<HostMessage>(unknown_bits).is_err());
+    let short_array = json!({
+        "message": "ProtocolInfoResponse",
+        "version": "1.2.0",
+        "spec_hash": 0,
+        "group": 1,
+        "supported_commands": [0, 1, 2],
+    });
+    assert!(serde_json::from_value::<DeviceMessage>(short_array).is_err());
+    let unknown_message = json!({ "message": "SelfDestruct" });
+    assert!(serde_json::from_value::<HostMessage>(unknown_message).is_err());
+}
+
+proptest! {
+    #[test]
+    fn host_messages_roundtrip(msg in any::<HostMessage>()) {
+        let json = serde_json::to_string(&msg).unwrap();
+        prop_assert_eq!(serde_json::from_str::<HostMessage>(&json).unwrap(), msg);
+    }
+
+    #[test]
+    fn device_messages_roundtrip(msg in any::<DeviceMessage>()) {
+        let json = serde_json::to_string(&msg).unwrap();
+        prop_assert_eq!(serde_json::from_str::<DeviceMessage>(&json).unwrap(), msg);
+    }
+}
@@ -1,10 +1,130 @@
-use protocol::Command;
+use protocol::{
+    DeviceMessage, ErrorCode, ErrorResponse, EventMask, GenericResponse, GetProtocolInfo,
+    HostMessage, LogLevel, Request, SetEventMask, Version, PROTOCOL_GROUPS, PROTOCOL_HASH,
+    PROTOCOL_VERSION,
+};
 use std::borrow::Cow;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::io::{ReadHalf, WriteHalf};
-use tokio_serial::{SerialPortBuilderExt, SerialStream};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_serial::SerialPortBuilderExt;
 
+#[cfg(test)]
+mod decoding;
+#[cfg(all(test, feature = "display"))]
+mod display;
+#[cfg(all(test, feature = "serde"))]
+mod json;
 pub mod protocol;
+#[cfg(test)]
+mod requests;
+#[cfg(test)]
+mod roundtrip;
+#[cfg(test)]
+mod vectors;
+
+/// How long [Atmosensor::request] waits for the device to answer
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum RequestError {
+    Io(std::io::Error),
+    /// The device didn't answer before the timeout elapsed
+    Timeout,
+    /// The device answered with an unsuccessful `GenericResponse` instead of the response
+    Rejected,
+    /// The device answered with an `ErrorResponse` saying why it failed the request
+    Failed(DeviceError),
+    /// The device speaks this version of the protocol, which this host can't talk to
+    Incompatible(String),
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestError::Io(err) => write!(f, "failed to send request: {err}"),
+            RequestError::Timeout => write!(f, "timed out waiting for a response"),
+            RequestError::Rejected => write!(f, "device rejected the request"),
+            RequestError::Failed(err) => write!(f, "{err}"),
+            RequestError::Incompatible(version) => write!(
+                f,
+                "device speaks protocol version {version}, which is incompatible with {PROTOCOL_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+impl From<std::io::Error> for RequestError {
+    fn from(err: std::io::Error) -> Self {
+        RequestError::Io(err)
+    }
+}
+
+/// Why the device failed a command, as reported by an `ErrorResponse`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceError {
+    pub code: ErrorCode,
+    /// Group and number of the command which failed
+    pub command: (u8, u8),
+}
+
+impl From<ErrorResponse> for DeviceError {
+    fn from(response: ErrorResponse) -> Self {
+        DeviceError {
+            code: response.code,
+            command: (response.failed_group, response.failed_command),
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (group, number) = self.command;
+        write!(
+            f,
+            "device failed command ({group:#04x}, {number:#04x}) with {:?}",
+            self.code
+        )
+    }
+}
+
+impl std::error::Error for DeviceError {}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => log::Level::Error,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+        }
+    }
+}
+
+/// Protocol revision and supported commands of a device, see [Atmosensor::handshake]
+#[derive(Clone, Debug)]
+pub struct ProtocolInfo {
+    pub version: String,
+    pub spec_hash: u32,
+    /// Bitmap of the supported command numbers of each group
+    supported_commands: HashMap<u8, [u8; 32]>,
+}
+
+impl ProtocolInfo {
+    /// Whether the device was built from exactly the same protocol definition as this host
+    pub fn matches_host(&self) -> bool {
+        self.spec_hash == PROTOCOL_HASH
+    }
+
+    /// Whether the device handles command `number` of `group`
+    pub fn supports(&self, group: u8, number: u8) -> bool {
+        self.supported_commands
+            .get(&group)
+            .is_some_and(|bitmap| bitmap[number as usize / 8] & (1 << (number % 8)) != 0)
+    }
+}
 
 pub struct Atmosensor {
     writer: Writer,
@@ -17,105 +137,274 @@ impl Atmosensor {
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let port = tokio_serial::new(serial_path, 115200).open_native_async()?;
         let (read_stream, write_stream) = tokio::io::split(port);
-        Ok(Self {
-            writer: Writer::new(write_stream),
-            reader: Reader::new(read_stream),
-        })
+        Ok(Self::from_streams(read_stream, write_stream))
+    }
+
+    fn from_streams(
+        read_stream: impl AsyncRead + Send + Unpin + 'static,
+        write_stream: impl AsyncWrite + Send + Unpin + 'static,
+    ) -> Self {
+        Self {
+            writer: Writer::new(Box::new(write_stream)),
+            reader: Reader::new(Box::new(read_stream)),
+        }
     }
 
     pub fn split(self) -> (Reader, Writer) {
         (self.reader, self.writer)
     }
 
-    pub async fn send(&mut self, cmd: Command) -> std::io::Result<()> {
-        self.writer.send(cmd).await
+    /// Sends `msg`, returning the sequence id of the frame it was sent in
+    pub async fn send(&mut self, msg: impl Into<HostMessage>) -> std::io::Result<u8> {
+        self.writer.send(msg).await
     }
 
-    pub async fn receive_next(&mut self, timeout: std::time::Duration) -> Option<Command> {
+    pub async fn receive_next(&mut self, timeout: std::time::Duration) -> Option<DeviceMessage> {
         self.reader.receive_next(timeout).await
     }
+
+    /// Number of frames which were dropped so far because they were corrupted on the way
+    pub fn corrupt_frames(&self) -> u64 {
+        self.reader.corrupt_frames()
+    }
+
+    /// Limits the messages which the device sends on its own to the kinds in `mask`. Replies
+    /// to requests are sent regardless.
+    pub async fn subscribe(&mut self, mask: EventMask) -> Result<(), RequestError> {
+        self.request(SetEventMask { mask }).await?;
+        Ok(())
+    }
+
+    /// Asks the device which protocol revision it speaks and which commands it supports, failing
+    /// with [RequestError::Incompatible] if this host can't talk to it
+    pub async fn handshake(&mut self) -> Result<ProtocolInfo, RequestError> {
+        let mut info = None;
+        for group in PROTOCOL_GROUPS {
+            let response = self.request(GetProtocolInfo { group }).await?;
+            let compatible = match (response.version.parse(), PROTOCOL_VERSION.parse()) {
+                (Ok(device), Ok(host)) => Version::compatible_with(device, host),
+                _ => false,
+            };
+            if !compatible {
+                return Err(RequestError::Incompatible(response.version));
+            }
+            info.get_or_insert_with(|| ProtocolInfo {
+                version: response.version,
+                spec_hash: response.spec_hash,
+                supported_commands: HashMap::new(),
+            })
+            .supported_commands
+            .insert(group, response.supported_commands);
+        }
+        Ok(info.expect("the protocol has at least one group"))
+    }
+
+    /// Sends `req` and waits for the response which echoes its sequence id. Anything else
+    /// received in the meantime is kept for later calls to [Atmosensor::receive_next].
+    pub async fn request<R: Request>(&mut self, req: R) -> Result<R::Response, RequestError> {
+        self.request_with_timeout(req, DEFAULT_REQUEST_TIMEOUT)
+            .await
+    }
+
+    pub async fn request_with_timeout<R: Request>(
+        &mut self,
+        req: R,
+        timeout: Duration,
+    ) -> Result<R::Response, RequestError> {
+        let sequence = self.writer.send(req).await?;
+
+        // Only new frames are read while waiting, the backlog is left alone until the request
+        // is done so that its messages aren't read again and again
+        let mut skipped = Vec::new();
+        let deadline = Instant::now() + timeout;
+        let result = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Ok((reply_sequence, msg)) =
+                tokio::time::timeout(remaining, self.reader.receive_new()).await
+            else {
+                break Err(RequestError::Timeout);
+            };
+            if reply_sequence != sequence {
+                skipped.push((reply_sequence, msg));
+                continue;
+            }
+            // Checked before the conversion, a failed GenericResponse is the response of
+            // acknowledged requests as far as the types go
+            match msg {
+                DeviceMessage::GenericResponse(GenericResponse { successful: false }) => {
+                    break Err(RequestError::Rejected)
+                }
+                DeviceMessage::ErrorResponse(response) => {
+                    break Err(RequestError::Failed(response.into()))
+                }
+                msg => match R::Response::try_from(msg) {
+                    Ok(response) => break Ok(response),
+                    Err(other) => skipped.push((reply_sequence, other)),
+                },
+            }
+        };
+        self.reader.backlog.extend(skipped);
+        result
+    }
+}
+
+/// Reasons a frame from the device was dropped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// The frame isn't valid COBS
+    Encoding,
+    /// The frame ended before its sequence id
+    MissingSequence,
+    /// The frame's CRC doesn't match, it was corrupted on the way
+    Crc,
+}
+
+/// Frames a message with its sequence id and CRC, COBS-encoded and terminated by 0x00
+pub fn encode_frame(sequence: u8, message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(message.len() + 3);
+    frame.push(sequence);
+    frame.extend_from_slice(message);
+    frame.extend_from_slice(&protocol::crc16(&frame).to_be_bytes());
+    let mut encoded = cobs::encode_vec(&frame);
+    encoded.push(0x00);
+    encoded
+}
+
+/// Decodes a COBS-encoded frame into `buffer` and checks its CRC, returning its sequence id and
+/// message. A `buffer` as long as `encoded` always fits the frame.
+pub fn decode_frame<'a>(
+    encoded: &[u8],
+    buffer: &'a mut [u8],
+) -> Result<(u8, &'a [u8]), FrameError> {
+    let bytes_decoded = cobs::decode(encoded, buffer).map_err(|_| FrameError::Encoding)?;
+    match protocol::strip_crc(&buffer[..bytes_decoded]) {
+        Some([sequence, message @ ..]) => Ok((*sequence, message)),
+        Some([]) => Err(FrameError::MissingSequence),
+        None => Err(FrameError::Crc),
+    }
 }
 
 pub struct Reader {
-    read_stream: ReadHalf<SerialStream>,
+    read_stream: Box<dyn AsyncRead + Send + Unpin>,
+    /// Messages and their sequence ids which arrived while waiting on a request's response
+    backlog: VecDeque<(u8, DeviceMessage)>,
+    /// Frames dropped because their CRC didn't match
+    corrupt_frames: u64,
     encoded_rx_buffer: Box<[u8; 1024]>,
     decoded_rx_buffer: Box<[u8; 1024]>,
 }
 
 impl Reader {
-    fn new(stream: ReadHalf<SerialStream>) -> Self {
+    fn new(stream: Box<dyn AsyncRead + Send + Unpin>) -> Self {
         Self {
             read_stream: stream,
+            backlog: VecDeque::new(),
+            corrupt_frames: 0,
             encoded_rx_buffer: Box::new([0u8; 1024]),
             decoded_rx_buffer: Box::new([0u8; 1024]),
         }
     }
 
-    pub async fn receive_next(&mut self, timeout: std::time::Duration) -> Option<Command> {
-        match tokio::time::timeout(timeout, self.receive()).await {
-            Ok(cmd) => Some(cmd),
-            Err(_) => None,
+    /// Number of frames which were dropped so far because they were corrupted on the way
+    pub fn corrupt_frames(&self) -> u64 {
+        self.corrupt_frames
+    }
+
+    pub async fn receive_next(&mut self, timeout: std::time::Duration) -> Option<DeviceMessage> {
+        tokio::time::timeout(timeout, self.receive()).await.ok()
+    }
+
+    pub async fn receive(&mut self) -> DeviceMessage {
+        self.receive_with_sequence().await.1
+    }
+
+    /// Receives the next message along with the sequence id of its frame, which is the id of
+    /// the host's frame it answers or [protocol::UNSOLICITED_SEQUENCE]
+    pub async fn receive_with_sequence(&mut self) -> (u8, DeviceMessage) {
+        match self.backlog.pop_front() {
+            Some(received) => received,
+            None => self.receive_new().await,
         }
     }
 
-    pub async fn receive(&mut self) -> Command {
+    /// Receives the next message from the device, passing over the backlog
+    async fn receive_new(&mut self) -> (u8, DeviceMessage) {
         loop {
-            if let Ok(bytes_read) = self.read_stream.read(&mut *self.encoded_rx_buffer).await {
-                if let Ok(bytes_decoded) = cobs::decode(
-                    &self.encoded_rx_buffer[..bytes_read],
-                    &mut *self.decoded_rx_buffer,
-                ) {
-                    // todo: Revisit failure cases here
-                    break Command::from_bytes(&self.decoded_rx_buffer[..bytes_decoded]);
-                } else {
-                    log::error!("Failed to decode {bytes_read} bytes");
-                }
-            } else {
-                log::error!("Failed to read from the serial port");
+            let (sequence, data) = self.receive_frame().await;
+            match DeviceMessage::from_bytes(&data) {
+                Ok(msg) => break (sequence, msg),
+                Err(err) => log::error!("Dropping frame which failed to decode: {err}"),
             }
         }
     }
 
+    /// Receives the next message whose CRC matches, without decoding it or its sequence id
     pub async fn receive_raw(&mut self) -> Vec<u8> {
+        self.receive_frame().await.1
+    }
+
+    async fn receive_frame(&mut self) -> (u8, Vec<u8>) {
         loop {
-            if let Ok(bytes_read) = self.read_stream.read(&mut *self.encoded_rx_buffer).await {
-                if let Ok(bytes_decoded) = cobs::decode(
-                    &self.encoded_rx_buffer[..bytes_read],
-                    &mut *self.decoded_rx_buffer,
-                ) {
-                    break Vec::from(&self.decoded_rx_buffer[..bytes_decoded]);
-                } else {
-                    log::error!("Failed to decode {bytes_read} bytes");
-                }
-            } else {
+            let Ok(bytes_read) = self.read_stream.read(&mut *self.encoded_rx_buffer).await else {
                 log::error!("Failed to read from the serial port");
+                continue;
+            };
+            match decode_frame(
+                &self.encoded_rx_buffer[..bytes_read],
+                &mut *self.decoded_rx_buffer,
+            ) {
+                Ok((sequence, message)) => break (sequence, Vec::from(message)),
+                Err(FrameError::Encoding) => log::error!("Failed to decode {bytes_read} bytes"),
+                Err(FrameError::MissingSequence) => {
+                    log::error!("Dropping frame without a sequence id")
+                }
+                Err(FrameError::Crc) => {
+                    self.corrupt_frames += 1;
+                    log::error!(
+                        "Dropping frame with a bad CRC, {} dropped so far",
+                        self.corrupt_frames
+                    );
+                }
             }
         }
     }
 }
 
 pub struct Writer {
-    write_stream: WriteHalf<SerialStream>,
-    encoded_tx_buffer: Box<[u8; 1024]>,
+    write_stream: Box<dyn AsyncWrite + Send + Unpin>,
+    /// Sequence id of the next frame
+    next_sequence: u8,
 }
 
 impl Writer {
-    fn new(stream: WriteHalf<SerialStream>) -> Self {
+    fn new(stream: Box<dyn AsyncWrite + Send + Unpin>) -> Self {
         Self {
             write_stream: stream,
-            encoded_tx_buffer: Box::new([0u8; 1024]),
+            next_sequence: 1,
         }
     }
 
-    pub async fn send(&mut self, cmd: Command) -> std::io::Result<()> {
-        self.send_raw(&cmd.to_bytes()).await
+    /// Sends `msg`, returning the sequence id of the frame it was sent in
+    pub async fn send(&mut self, msg: impl Into<HostMessage>) -> std::io::Result<u8> {
+        let data = msg
+            .into()
+            .to_bytes()
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+        self.send_raw(&data).await
     }
 
-    pub async fn send_raw(&mut self, data: &[u8]) -> std::io::Result<()> {
-        let bytes_encoded = cobs::encode(data, &mut *self.encoded_tx_buffer);
-        self.encoded_tx_buffer[bytes_encoded] = 0x00;
+    /// Sends an already encoded message, adding its sequence id, CRC and framing. Returns the
+    /// sequence id.
+    pub async fn send_raw(&mut self, data: &[u8]) -> std::io::Result<u8> {
+        let sequence = self.next_sequence;
+        self.next_sequence = match self.next_sequence.wrapping_add(1) {
+            protocol::UNSOLICITED_SEQUENCE => 1,
+            next => next,
+        };
+
         self.write_stream
-            .write_all(&self.encoded_tx_buffer[..=bytes_encoded])
-            .await
+            .write_all(&encode_frame(sequence, data))
+            .await?;
+        Ok(sequence)
     }
 }
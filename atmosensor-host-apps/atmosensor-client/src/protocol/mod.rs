@@ -1,2 +1,7 @@
-mod autogen;
+//! Messages of the USB protocol, generated from `usb-protocol/protocol.json5` by `build.rs`.
+
+#[allow(dead_code, unused_mut)]
+mod autogen {
+    include!(concat!(env!("OUT_DIR"), "/protocol.rs"));
+}
 pub use autogen::*;
@@ -0,0 +1,143 @@
+//! Checks that requests pick their response out of everything else the device sends, and keep
+//! the rest for later, against a scripted device instead of a serial port.
+
+use crate::protocol::{
+    DeviceMessage, ErrorCode, ErrorResponse, GenericResponse, LogLevel, LogMessage, PingResponse,
+    ReportNewData, SetMeasurementPush, UNSOLICITED_SEQUENCE,
+};
+use crate::{Atmosensor, DeviceError, RequestError};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Sequence id of the first frame the host sends
+const FIRST_SEQUENCE: u8 = 1;
+
+/// Hands out one frame per read, then never becomes readable again
+struct ScriptedDevice(VecDeque<Vec<u8>>);
+
+impl AsyncRead for ScriptedDevice {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.0.pop_front() {
+            Some(frame) => {
+                buf.put_slice(&frame);
+                Poll::Ready(Ok(()))
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+fn device(frames: &[(u8, DeviceMessage)]) -> Atmosensor {
+    let frames = frames
+        .iter()
+        .map(|(sequence, msg)| crate::encode_frame(*sequence, &msg.clone().to_bytes().unwrap()))
+        .collect();
+    Atmosensor::from_streams(ScriptedDevice(frames), tokio::io::sink())
+}
+
+/// Runs `test` on its own thread and fails if it doesn't finish in time, so that a request
+/// which never yields fails the test rather than hanging it
+fn with_deadline(test: impl Future<Output = ()> + Send + 'static) {
+    let (done, finished) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(test);
+        done.send(()).unwrap();
+    });
+    finished
+        .recv_timeout(Duration::from_secs(5))
+        .expect("test didn't finish in time");
+}
+
+fn log_message() -> DeviceMessage {
+    DeviceMessage::LogMessage(LogMessage {
+        level: LogLevel::Info,
+        text: "measuring".to_owned(),
+    })
+}
+
+#[test]
+fn messages_before_the_response_are_kept() {
+    with_deadline(async {
+        let mut atmosensor = device(&[
+            (UNSOLICITED_SEQUENCE, ReportNewData {}.into()),
+            (UNSOLICITED_SEQUENCE, log_message()),
+            (FIRST_SEQUENCE + 1, PingResponse {}.into()),
+            (FIRST_SEQUENCE, GenericResponse { successful: true }.into()),
+        ]);
+        let response = atmosensor
+            .request(SetMeasurementPush { enabled: true })
+            .await;
+        assert_eq!(response.unwrap(), GenericResponse { successful: true });
+
+        let timeout = Duration::from_millis(10);
+        let expected = [
+            ReportNewData {}.into(),
+            log_message(),
+            PingResponse {}.into(),
+        ];
+        for expected in expected {
+            assert_eq!(atmosensor.receive_next(timeout).await, Some(expected));
+        }
+        assert_eq!(atmosensor.receive_next(timeout).await, None);
+    });
+}
+
+#[test]
+fn messages_are_kept_when_the_request_times_out() {
+    with_deadline(async {
+        let mut atmosensor = device(&[(UNSOLICITED_SEQUENCE, ReportNewData {}.into())]);
+        let response = atmosensor
+            .request_with_timeout(
+                SetMeasurementPush { enabled: true },
+                Duration::from_millis(50),
+            )
+            .await;
+        assert!(matches!(response, Err(RequestError::Timeout)));
+
+        let received = atmosensor.receive_next(Duration::from_millis(10)).await;
+        assert_eq!(received, Some(ReportNewData {}.into()));
+    });
+}
+
+#[test]
+fn unsuccessful_acknowledgement_is_rejected() {
+    with_deadline(async {
+        let mut atmosensor =
+            device(&[(FIRST_SEQUENCE, GenericResponse { successful: false }.into())]);
+        let response = atmosensor
+            .request(SetMeasurementPush { enabled: true })
+            .await;
+        assert!(matches!(response, Err(RequestError::Rejected)));
+    });
+}
+
+#[test]
+fn error_response_fails_the_request() {
+    with_deadline(async {
+        let error = ErrorResponse {
+            code: ErrorCode::SensorTimeout,
+            failed_group: SetMeasurementPush::GROUP,
+            failed_command: SetMeasurementPush::NUMBER,
+        };
+        let mut atmosensor = device(&[(FIRST_SEQUENCE, error.clone().into())]);
+        let response = atmosensor
+            .request(SetMeasurementPush { enabled: true })
+            .await;
+        let Err(RequestError::Failed(err)) = response else {
+            panic!("expected the request to fail, got {response:?}");
+        };
+        assert_eq!(err, DeviceError::from(error));
+    });
+}
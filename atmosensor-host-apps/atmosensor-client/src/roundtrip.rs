@@ -0,0 +1,63 @@
+//! Checks that every message survives being encoded and decoded again, on its own and inside a
+//! frame, and that bad input is rejected rather than panicking. The fuzz targets in `fuzz`
+//! exercise the same decoding for longer.
+
+use crate::protocol::{crc16, DeviceMessage, HostMessage};
+use proptest::prelude::*;
+
+/// Frames a message the way the device does, before it's COBS-encoded
+fn frame(sequence: u8, message: &[u8]) -> Vec<u8> {
+    let mut frame = vec![sequence];
+    frame.extend_from_slice(message);
+    frame.extend_from_slice(&crc16(&frame).to_be_bytes());
+    frame
+}
+
+proptest! {
+    #[test]
+    fn host_messages_roundtrip(msg in any::<HostMessage>()) {
+        let bytes = msg.clone().to_bytes().unwrap();
+        prop_assert_eq!(HostMessage::from_bytes(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn device_messages_roundtrip(msg in any::<DeviceMessage>()) {
+        let bytes = msg.clone().to_bytes().unwrap();
+        prop_assert_eq!(DeviceMessage::from_bytes(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn frames_roundtrip(sequence in any::<u8>(), msg in any::<DeviceMessage>()) {
+        let bytes = msg.clone().to_bytes().unwrap();
+        let encoded = crate::encode_frame(sequence, &bytes);
+        let mut buffer = vec![0; encoded.len()];
+        let (decoded_sequence, decoded) = crate::decode_frame(&encoded, &mut buffer).unwrap();
+        prop_assert_eq!(decoded_sequence, sequence);
+        prop_assert_eq!(DeviceMessage::from_bytes(decoded).unwrap(), msg);
+    }
+
+    #[test]
+    fn corrupted_frames_are_dropped(
+        msg in any::<DeviceMessage>(),
+        index in any::<prop::sample::Index>(),
+        flip in 1..=u8::MAX,
+    ) {
+        let mut frame = frame(1, &msg.to_bytes().unwrap());
+        let corrupted = index.index(frame.len());
+        frame[corrupted] ^= flip;
+        let encoded = cobs::encode_vec(&frame);
+        let mut buffer = vec![0; encoded.len()];
+        prop_assert_eq!(
+            crate::decode_frame(&encoded, &mut buffer),
+            Err(crate::FrameError::Crc)
+        );
+    }
+
+    #[test]
+    fn arbitrary_bytes_decode_without_panicking(data in prop::collection::vec(any::<u8>(), 0..64)) {
+        let _ = HostMessage::from_bytes(&data);
+        let _ = DeviceMessage::from_bytes(&data);
+        let mut buffer = vec![0; data.len()];
+        let _ = crate::decode_frame(&data, &mut buffer);
+    }
+}
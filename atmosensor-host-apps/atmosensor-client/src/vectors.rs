@@ -0,0 +1,50 @@
+//! Checks the client's encoding against the golden test vectors in `usb-protocol/vectors.json5`.
+//! `atmosensor-fw-tests` checks the firmware's against the same vectors, so the two can't
+//! disagree about the wire format while each still round-trips its own messages.
+
+use crate::protocol::{ArgsError, DeviceMessage, HostMessage};
+use atmosensor_codegen::{load_vectors, TestVector};
+use std::path::Path;
+
+fn vectors() -> Vec<TestVector> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../usb-protocol/vectors.json5");
+    load_vectors(&path).unwrap()
+}
+
+#[test]
+fn messages_match_vectors() {
+    for vector in vectors() {
+        let context = format!("{} {:?}", vector.message, vector.args);
+        let args: Vec<_> = vector.args.iter().map(String::as_str).collect();
+        match HostMessage::from_args(&vector.message, &args) {
+            Ok(msg) => {
+                assert_eq!(msg.clone().to_bytes().unwrap(), vector.raw, "{context}");
+                assert_eq!(HostMessage::from_bytes(&vector.raw), Ok(msg), "{context}");
+            }
+            Err(ArgsError::UnknownMessage(_)) => {
+                let msg = DeviceMessage::from_args(&vector.message, &args).expect(&context);
+                assert_eq!(msg.clone().to_bytes().unwrap(), vector.raw, "{context}");
+                assert_eq!(DeviceMessage::from_bytes(&vector.raw), Ok(msg), "{context}");
+            }
+            Err(err) => panic!("{context}: {err}"),
+        }
+    }
+}
+
+#[test]
+fn frames_match_vectors() {
+    for vector in vectors() {
+        let context = format!("{} {:?}", vector.message, vector.args);
+        assert_eq!(
+            crate::encode_frame(vector.sequence, &vector.raw),
+            vector.frame,
+            "{context}"
+        );
+        let mut buffer = vec![0; vector.frame.len()];
+        assert_eq!(
+            crate::decode_frame(&vector.frame, &mut buffer),
+            Ok((vector.sequence, &vector.raw[..])),
+            "{context}"
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use convert_case::{Case, Casing};
+use std::collections::{HashMap, HashSet};
+
+use crate::spec::{
+    scalar_range, ParamType, Parameter, ProtocolFile, TypeKind, ERROR_RESPONSE, GENERIC_RESPONSE,
+    TYPE_REPRS,
+};
+
+/// Checks the protocol for mistakes which would otherwise only surface as broken generated
+/// code or as the firmware and hosts disagreeing, returning a message for each one found.
+pub fn check_protocol(protocol: &ProtocolFile) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let mut type_names = HashSet::new();
+    for ty in &protocol.types {
+        let location = format!("types.{}", ty.name);
+        if !type_names.insert(ty.name.as_str()) {
+            errors.push(format!("{location}: type is defined more than once"));
+        }
+        let max_value = match ty.repr.as_str() {
+            "u8" => u8::MAX as u32,
+            "u16" => u16::MAX as u32,
+            "u32" => u32::MAX,
+            other => {
+                errors.push(format!(
+                    "{location}: unsupported repr `{other}`, expected one of {TYPE_REPRS:?}"
+                ));
+                u32::MAX
+            }
+        };
+        if ty.values.is_empty() {
+            errors.push(format!("{location}: type has no values"));
+        }
+        let mut value_names = HashMap::new();
+        let mut values = HashMap::new();
+        for value in &ty.values {
+            let generated_name = match ty.kind {
+                TypeKind::Enum => value.name.clone(),
+                TypeKind::Bitflags => value.name.to_case(Case::UpperSnake),
+            };
+            if let Some(other) = value_names.insert(generated_name.clone(), &value.name) {
+                errors.push(format!(
+                    "{location}: values `{other}` and `{}` both generate `{generated_name}`",
+                    value.name
+                ));
+            }
+            if let Some(other) = values.insert(value.value, &value.name) {
+                errors.push(format!(
+                    "{location}: values `{other}` and `{}` are both {}",
+                    value.name, value.value
+                ));
+            }
+            if value.value > max_value {
+                errors.push(format!(
+                    "{location}: value `{}` ({}) does not fit in a {}",
+                    value.name, value.value, ty.repr
+                ));
+            }
+        }
+    }
+
+    let all_commands = protocol
+        .groups
+        .iter()
+        .flat_map(|grp| grp.commands.iter().map(move |cmd| (grp, cmd)))
+        .collect::<Vec<_>>();
+
+    let mut group_names = HashSet::new();
+    for grp in &protocol.groups {
+        if !group_names.insert(grp.group.as_str()) {
+            errors.push(format!("{}: group is defined more than once", grp.group));
+        }
+    }
+
+    let mut ids = HashMap::new();
+    let mut command_names = HashSet::new();
+    let mut responses = HashMap::new();
+    let mut handler_methods = HashMap::new();
+    for (grp, cmd) in &all_commands {
+        let location = format!("{}.{}", grp.group, cmd.name);
+        if let Some(other) = ids.insert((grp.number, cmd.number), location.clone()) {
+            errors.push(format!(
+                "{location}: (group, number) ({:#04x}, {:#04x}) is already used by {other}",
+                grp.number, cmd.number
+            ));
+        }
+        if !command_names.insert(cmd.name.as_str()) {
+            errors.push(format!("{location}: command name is used more than once"));
+        }
+        if type_names.contains(cmd.name.as_str()) {
+            errors.push(format!("{location}: command has the same name as a type"));
+        }
+        if cmd.direction.from_host() {
+            let method = cmd.name.to_case(Case::Snake);
+            if method == "not_supported" {
+                errors.push(format!(
+                    "{location}: Handler method `{method}` clashes with a generated method"
+                ));
+            }
+            if let Some(other) = handler_methods.insert(method.clone(), &cmd.name) {
+                errors.push(format!(
+                    "{location}: collides with command `{other}`, both become the Handler method `{method}`"
+                ));
+            }
+        }
+
+        match &cmd.associated_request {
+            Some(request) => {
+                match all_commands.iter().find(|(_, other)| &other.name == request) {
+                    Some((_, request)) if request.acknowledged => errors.push(format!(
+                        "{location}: associated_request `{}` is already acknowledged with {GENERIC_RESPONSE}",
+                        request.name
+                    )),
+                    Some((_, request)) if !request.direction.from_host() => errors.push(format!(
+                        "{location}: associated_request `{}` is never sent by the host",
+                        request.name
+                    )),
+                    Some(_) => {}
+                    None => errors.push(format!(
+                        "{location}: associated_request `{request}` is not a command"
+                    )),
+                }
+                if let Some(other) = responses.insert(request.as_str(), &cmd.name) {
+                    errors.push(format!(
+                        "{location}: `{request}` is already answered by `{other}`"
+                    ));
+                }
+            }
+            None => {
+                if cmd.name.ends_with("Response")
+                    && cmd.name != GENERIC_RESPONSE
+                    && cmd.name != ERROR_RESPONSE
+                {
+                    errors.push(format!("{location}: response has no associated_request"));
+                }
+            }
+        }
+
+        if cmd.associated_request.is_some() && !cmd.direction.from_device() {
+            errors.push(format!("{location}: response is never sent by the device"));
+        }
+        if [GENERIC_RESPONSE, ERROR_RESPONSE].contains(&cmd.name.as_str())
+            && !cmd.direction.from_device()
+        {
+            errors.push(format!("{location}: is never sent by the device"));
+        }
+        if cmd.acknowledged && !cmd.direction.from_host() {
+            errors.push(format!(
+                "{location}: acknowledged but never sent by the host"
+            ));
+        }
+
+        if cmd.acknowledged
+            && !all_commands
+                .iter()
+                .any(|(_, other)| other.name == GENERIC_RESPONSE)
+        {
+            errors.push(format!(
+                "{location}: acknowledged but there is no {GENERIC_RESPONSE} command"
+            ));
+        }
+
+        let mut param_names = HashMap::new();
+        let mut accessors = HashMap::new();
+        for param in &cmd.parameters {
+            let location = format!("{location}.{}", param.name);
+            let field_name = param.name.to_case(Case::Snake);
+            if let Some(other) = param_names.insert(field_name.clone(), &param.name) {
+                errors.push(format!(
+                    "{location}: collides with parameter `{other}`, both become `{field_name}`"
+                ));
+            }
+            let param_type = match ParamType::parse(&param.param_type, &protocol.types) {
+                Ok(param_type) => param_type,
+                Err(err) => {
+                    errors.push(format!("{location}: {err}"));
+                    continue;
+                }
+            };
+            if matches!(param_type, ParamType::String | ParamType::Bytes)
+                && param.max_length.is_none()
+            {
+                errors.push(format!(
+                    "{location}: `{}` parameters require a `max_length`",
+                    param.param_type
+                ));
+            }
+            check_units(&location, param, &param_type, &mut errors);
+            if let Some(unit) = &param.unit {
+                let accessor = format!("{field_name}_{unit}");
+                if GENERATED_METHODS.contains(&accessor.as_str()) {
+                    errors.push(format!(
+                        "{location}: accessor `{accessor}` clashes with a generated method"
+                    ));
+                }
+                if let Some(other) = accessors.insert(accessor.clone(), &param.name) {
+                    errors.push(format!(
+                        "{location}: accessor `{accessor}` is also generated for `{other}`"
+                    ));
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Methods which the generated messages have besides their unit accessors
+const GENERATED_METHODS: [&str; 6] = [
+    "new",
+    "from_bytes",
+    "to_bytes",
+    "decode",
+    "encode_into",
+    "check_ranges",
+];
+
+/// Checks the `unit`, `scale`, `min` and `max` of a parameter
+fn check_units(
+    location: &str,
+    param: &Parameter,
+    param_type: &ParamType,
+    errors: &mut Vec<String>,
+) {
+    let has_units =
+        param.unit.is_some() || param.scale.is_some() || param.min.is_some() || param.max.is_some();
+    let ParamType::Scalar(ty) = param_type else {
+        if has_units {
+            errors.push(format!(
+                "{location}: only numeric parameters can have a unit, scale or range"
+            ));
+        }
+        return;
+    };
+
+    if let Some(unit) = &param.unit {
+        let is_identifier = unit.starts_with(|ch: char| ch.is_ascii_lowercase())
+            && unit
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_');
+        if !is_identifier {
+            errors.push(format!(
+                "{location}: unit `{unit}` must be lowercase letters, digits and underscores"
+            ));
+        }
+    } else if param.scale.is_some() {
+        errors.push(format!("{location}: a scale needs a unit"));
+    }
+    if let Some(scale) = param.scale {
+        if !(scale.is_finite() && scale > 0.0) {
+            errors.push(format!("{location}: scale {scale} must be positive"));
+        }
+    }
+
+    // An invalid scale was reported above, carry on with the ranges as if it wasn't there
+    let scale = param
+        .scale
+        .filter(|scale| scale.is_finite() && *scale > 0.0)
+        .unwrap_or(1.0);
+    let (type_min, type_max) = scalar_range(ty);
+    if let Some(min) = param.min {
+        if min / scale < type_min {
+            errors.push(format!(
+                "{location}: min {min} is below what a `{ty}` can carry"
+            ));
+        }
+    }
+    if let Some(max) = param.max {
+        if max / scale > type_max {
+            errors.push(format!(
+                "{location}: max {max} is above what a `{ty}` can carry"
+            ));
+        }
+    }
+    if let (Some(min), Some(max)) = (param.min, param.max) {
+        if min > max {
+            errors.push(format!("{location}: min {min} is above max {max}"));
+            return;
+        }
+    }
+    if let Some((min, max)) = param.raw_range(param_type) {
+        if min > max {
+            errors.push(format!("{location}: no raw value is within min and max"));
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use crate::spec::{Command, CommandGroup, Direction, ProtocolFile, TypeDefinition};
+use crate::version::Version;
+
+/// How a change between two revisions of the protocol affects hosts and firmware which were
+/// built from different revisions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// Neither side notices, e.g. a renamed parameter
+    Compatible,
+    /// Something new which only peers built from the new revision use
+    Additive,
+    /// Peers built from the two revisions no longer understand each other
+    Breaking,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Compatible => write!(f, "compatible"),
+            ChangeKind::Additive => write!(f, "additive"),
+            ChangeKind::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub description: String,
+}
+
+impl Change {
+    fn new(kind: ChangeKind, description: String) -> Self {
+        Self { kind, description }
+    }
+}
+
+/// Lists how `new` differs from `old`. Commands and types are matched by name, parameters by
+/// their position since that's how they're laid out on the wire. Descriptions are ignored.
+pub fn diff_protocols(old: &ProtocolFile, new: &ProtocolFile) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_types(&old.types, &new.types, &mut changes);
+
+    for grp in &new.groups {
+        if !old.groups.iter().any(|other| other.number == grp.number) {
+            changes.push(Change::new(
+                ChangeKind::Additive,
+                format!("{}: new group {:#04x}", grp.group, grp.number),
+            ));
+        }
+    }
+
+    let old_commands = commands_by_name(old);
+    let new_commands = commands_by_name(new);
+    for (name, (grp, _)) in &old_commands {
+        if !new_commands.contains_key(name) {
+            changes.push(Change::new(
+                ChangeKind::Breaking,
+                format!("{}.{name}: command was removed", grp.group),
+            ));
+        }
+    }
+    for (name, (grp, cmd)) in &new_commands {
+        match old_commands.get(name) {
+            Some((old_grp, old_cmd)) => diff_command(old_grp, old_cmd, grp, cmd, &mut changes),
+            None => changes.push(Change::new(
+                ChangeKind::Additive,
+                format!("{}.{name}: new command", grp.group),
+            )),
+        }
+    }
+
+    changes.sort_by(|a, b| b.kind.cmp(&a.kind).then(a.description.cmp(&b.description)));
+    changes
+}
+
+fn commands_by_name(protocol: &ProtocolFile) -> HashMap<&str, (&CommandGroup, &Command)> {
+    protocol
+        .groups
+        .iter()
+        .flat_map(|grp| {
+            grp.commands
+                .iter()
+                .map(move |cmd| (cmd.name.as_str(), (grp, cmd)))
+        })
+        .collect()
+}
+
+fn diff_types(old: &[TypeDefinition], new: &[TypeDefinition], changes: &mut Vec<Change>) {
+    for old_ty in old {
+        let location = format!("types.{}", old_ty.name);
+        let Some(new_ty) = new.iter().find(|ty| ty.name == old_ty.name) else {
+            // Parameters which used it show up as changed types
+            changes.push(Change::new(
+                ChangeKind::Compatible,
+                format!("{location}: type was removed"),
+            ));
+            continue;
+        };
+        if old_ty.kind != new_ty.kind {
+            changes.push(Change::new(
+                ChangeKind::Breaking,
+                format!("{location}: kind changed"),
+            ));
+        }
+        if old_ty.repr != new_ty.repr {
+            changes.push(Change::new(
+                ChangeKind::Breaking,
+                format!(
+                    "{location}: repr changed from {} to {}",
+                    old_ty.repr, new_ty.repr
+                ),
+            ));
+        }
+        for old_value in &old_ty.values {
+            match new_ty
+                .values
+                .iter()
+                .find(|value| value.name == old_value.name)
+            {
+                Some(new_value) if new_value.value != old_value.value => changes.push(Change::new(
+                    ChangeKind::Breaking,
+                    format!(
+                        "{location}.{}: value changed from {} to {}",
+                        old_value.name, old_value.value, new_value.value
+                    ),
+                )),
+                Some(_) => {}
+                None => changes.push(Change::new(
+                    ChangeKind::Breaking,
+                    format!("{location}.{}: value was removed", old_value.name),
+                )),
+            }
+        }
+        // Older peers' decoders reject values and bits they don't know, dropping the whole
+        // message which carries one
+        for new_value in &new_ty.values {
+            if !old_ty
+                .values
+                .iter()
+                .any(|value| value.name == new_value.name)
+            {
+                changes.push(Change::new(
+                    ChangeKind::Breaking,
+                    format!(
+                        "{location}.{}: new value {}",
+                        new_value.name, new_value.value
+                    ),
+                ));
+            }
+        }
+    }
+    for new_ty in new {
+        if !old.iter().any(|ty| ty.name == new_ty.name) {
+            changes.push(Change::new(
+                ChangeKind::Additive,
+                format!("types.{}: new type", new_ty.name),
+            ));
+        }
+    }
+}
+
+fn diff_command(
+    old_grp: &CommandGroup,
+    old: &Command,
+    grp: &CommandGroup,
+    new: &Command,
+    changes: &mut Vec<Change>,
+) {
+    let location = format!("{}.{}", grp.group, new.name);
+    let mut push = |kind, description: String| {
+        changes.push(Change::new(kind, format!("{location}: {description}")));
+    };
+
+    if (old_grp.number, old.number) != (grp.number, new.number) {
+        push(
+            ChangeKind::Breaking,
+            format!(
+                "renumbered from ({:#04x}, {:#04x}) to ({:#04x}, {:#04x})",
+                old_grp.number, old.number, grp.number, new.number
+            ),
+        );
+    }
+    if old.direction != new.direction {
+        // Widening to `both` only lets the other side send something new
+        let kind = if new.direction == Direction::Both {
+            ChangeKind::Additive
+        } else {
+            ChangeKind::Breaking
+        };
+        push(
+            kind,
+            format!(
+                "direction changed from {} to {}",
+                old.direction, new.direction
+            ),
+        );
+    }
+    if old.associated_request != new.associated_request {
+        push(
+            ChangeKind::Breaking,
+            format!(
+                "associated_request changed from {:?} to {:?}",
+                old.associated_request, new.associated_request
+            ),
+        );
+    }
+    if old.acknowledged != new.acknowledged {
+        push(
+            ChangeKind::Breaking,
+            format!(
+                "acknowledged changed from {} to {}",
+                old.acknowledged, new.acknowledged
+            ),
+        );
+    }
+
+    if old.parameters.len() != new.parameters.len() {
+        push(
+            ChangeKind::Breaking,
+            format!(
+                "has {} parameters instead of {}",
+                new.parameters.len(),
+                old.parameters.len()
+            ),
+        );
+    }
+    for (old_param, new_param) in old.parameters.iter().zip(&new.parameters) {
+        let name = &new_param.name;
+        if old_param.param_type != new_param.param_type {
+            push(
+                ChangeKind::Breaking,
+                format!(
+                    "parameter `{name}` changed type from `{}` to `{}`",
+                    old_param.param_type, new_param.param_type
+                ),
+            );
+        }
+        if old_param.max_length != new_param.max_length {
+            push(
+                ChangeKind::Breaking,
+                format!(
+                    "parameter `{name}` changed max_length from {:?} to {:?}",
+                    old_param.max_length, new_param.max_length
+                ),
+            );
+        }
+        if old_param.unit != new_param.unit || old_param.scale != new_param.scale {
+            push(
+                ChangeKind::Breaking,
+                format!("parameter `{name}` changed what its raw value means"),
+            );
+        }
+        if old_param.min != new_param.min || old_param.max != new_param.max {
+            push(
+                ChangeKind::Compatible,
+                format!("parameter `{name}` changed its allowed range"),
+            );
+        }
+        if old_param.name != new_param.name {
+            push(
+                ChangeKind::Compatible,
+                format!("parameter `{}` was renamed to `{name}`", old_param.name),
+            );
+        }
+    }
+}
+
+/// Checks that `new`'s version is bumped far enough for `changes`: breaking changes need an
+/// incompatible version and any other change needs a newer one
+pub fn check_version_bump(
+    old: &ProtocolFile,
+    new: &ProtocolFile,
+    changes: &[Change],
+) -> Result<(), String> {
+    let old_version = old.version.parse::<Version>()?;
+    let new_version = new.version.parse::<Version>()?;
+    if new_version < old_version {
+        return Err(format!(
+            "version went backwards from {} to {}",
+            old.version, new.version
+        ));
+    }
+    let Some(worst) = changes.iter().map(|change| change.kind).max() else {
+        return Ok(());
+    };
+    if worst == ChangeKind::Breaking && new_version.compatible_with(old_version) {
+        return Err(format!(
+            "breaking changes need an incompatible version, but {} is compatible with {}",
+            new.version, old.version
+        ));
+    }
+    if new_version == old_version {
+        return Err(format!(
+            "{worst} changes need a newer version than {}",
+            old.version
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use convert_case::{Case, Casing};
+use minijinja::context;
+use serde::Serialize;
+
+use crate::spec::{Command, ParamType, Parameter, ProtocolFile, TypeDefinition, TypeKind};
+
+const DISSECTOR_TMPL: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/dissector.lua.j2"
+));
+
+impl ParamType {
+    /// Lua expression constructing the Wireshark `ProtoField` which displays the parameter
+    pub(crate) fn proto_field(
+        &self,
+        types: &[TypeDefinition],
+        abbrev: &str,
+        param: &Parameter,
+    ) -> String {
+        let name = lua_string(&param.name);
+        let description = lua_string(&param.description);
+        let integer = |ty: &str| match ty {
+            "u8" => "uint8",
+            "i8" => "int8",
+            "u16" => "uint16",
+            "i16" => "int16",
+            "u32" => "uint32",
+            _ => "int32",
+        };
+        match self {
+            ParamType::Bool => {
+                format!("ProtoField.bool(\"{abbrev}\", {name}, base.NONE, nil, nil, {description})")
+            }
+            ParamType::Scalar("f32") => {
+                format!("ProtoField.float(\"{abbrev}\", {name}, nil, {description})")
+            }
+            ParamType::Scalar(ty) => format!(
+                "ProtoField.{}(\"{abbrev}\", {name}, base.DEC, nil, nil, {description})",
+                integer(ty)
+            ),
+            ParamType::ByteArray(_) | ParamType::Bytes => {
+                format!("ProtoField.bytes(\"{abbrev}\", {name}, base.NONE, {description})")
+            }
+            ParamType::String => {
+                format!("ProtoField.string(\"{abbrev}\", {name}, base.UNICODE, {description})")
+            }
+            ParamType::Named { name: ty, repr } => {
+                let kind = types
+                    .iter()
+                    .find(|other| &other.name == ty)
+                    .map(|ty| ty.kind);
+                let (display, values) = match kind {
+                    Some(TypeKind::Enum) => {
+                        ("base.DEC", format!("{}_values", ty.to_case(Case::Snake)))
+                    }
+                    _ => ("base.HEX", "nil".to_owned()),
+                };
+                format!(
+                    "ProtoField.{}(\"{abbrev}\", {name}, {display}, {values}, nil, {description})",
+                    integer(repr)
+                )
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DissectorGroup {
+    number: u8,
+    messages: Vec<DissectorMessage>,
+}
+
+#[derive(Serialize)]
+struct DissectorMessage {
+    name: String,
+    number: u8,
+    params: Vec<DissectorParam>,
+}
+
+#[derive(Serialize)]
+struct DissectorParam {
+    /// Lua expression for the parameter's `ProtoField`
+    field: String,
+    /// `None` for length-prefixed parameters
+    size: Option<usize>,
+    encoding: Option<&'static str>,
+    /// Lua expression for the fields of each bit, if it's a bitflags parameter
+    flags: Option<String>,
+}
+
+fn dissect_param(types: &[TypeDefinition], cmd: &Command, param: &Parameter) -> DissectorParam {
+    let param_type = ParamType::parse(&param.param_type, types).unwrap();
+    let abbrev = format!(
+        "atmosensor.{}.{}",
+        cmd.name.to_case(Case::Snake),
+        param.name.to_case(Case::Snake)
+    );
+    let flags = match &param_type {
+        ParamType::Named { name, .. } => types
+            .iter()
+            .find(|ty| &ty.name == name && ty.kind == TypeKind::Bitflags)
+            .map(|ty| {
+                format!(
+                    "flag_fields(\"{abbrev}\", {}, {}_flags)",
+                    param_type.wire_size().unwrap() * 8,
+                    ty.name.to_case(Case::Snake)
+                )
+            }),
+        _ => None,
+    };
+    DissectorParam {
+        field: param_type.proto_field(types, &abbrev, param),
+        size: param_type.wire_size(),
+        encoding: (param_type == ParamType::String).then_some("ENC_UTF_8"),
+        flags,
+    }
+}
+
+/// Renders a Wireshark Lua dissector for the protocol
+pub fn generate_dissector(protocol: &ProtocolFile) -> Result<String, crate::Error> {
+    let groups = protocol
+        .groups
+        .iter()
+        .map(|grp| DissectorGroup {
+            number: grp.number,
+            messages: grp
+                .commands
+                .iter()
+                .map(|cmd| DissectorMessage {
+                    name: cmd.name.clone(),
+                    number: cmd.number,
+                    params: cmd
+                        .parameters
+                        .iter()
+                        .map(|param| dissect_param(&protocol.types, cmd, param))
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut env = minijinja::Environment::new();
+    env.add_template("dissector", DISSECTOR_TMPL)?;
+    env.add_filter("hex", |value: u32| format!("{value:#04x}"));
+    env.add_filter("lua_string", |value: String| lua_string(&value));
+    env.add_filter("param_case", |value: String| value.to_case(Case::Snake));
+    let rendered = env.get_template("dissector")?.render(context! {
+        protocol => protocol,
+        groups => groups,
+    })?;
+    Ok(rendered)
+}
+
+/// Quotes `value` as a Lua string literal, Rust's escapes are all valid Lua
+pub(crate) fn lua_string(value: &str) -> String {
+    format!("{value:?}")
+}
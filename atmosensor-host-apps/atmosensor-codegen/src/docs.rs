@@ -0,0 +1,226 @@
+use minijinja::context;
+use serde::Serialize;
+
+use crate::spec::{
+    Command, CommandGroup, Direction, ParamType, Parameter, ProtocolFile, GENERIC_RESPONSE,
+};
+
+const DOCS_TMPL: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/protocol.md.j2"
+));
+
+impl ParamType {
+    /// Human readable description of the wire format for the reference documentation
+    pub(crate) fn describe(&self, max_length: Option<u8>) -> String {
+        match self {
+            ParamType::Bool => "`bool`, zero is false".to_owned(),
+            ParamType::Scalar(ty) if self.wire_size() == Some(1) => format!("`{ty}`"),
+            ParamType::Scalar(ty) => format!("`{ty}`, big-endian"),
+            ParamType::ByteArray(len) => format!("`u8[{len}]`"),
+            ParamType::String => format!(
+                "`string`, `u8` length then up to {} bytes of UTF-8",
+                max_length.unwrap_or_default()
+            ),
+            ParamType::Bytes => format!(
+                "`bytes`, `u8` length then up to {} bytes",
+                max_length.unwrap_or_default()
+            ),
+            ParamType::Named { name, repr } => {
+                format!("[`{name}`](#{}) as `{repr}`", name.to_lowercase())
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GroupDoc {
+    name: String,
+    number: u8,
+    messages: Vec<MessageDoc>,
+}
+
+#[derive(Serialize)]
+struct MessageDoc {
+    name: String,
+    description: String,
+    group: u8,
+    number: u8,
+    direction: &'static str,
+    /// Message which the device answers this one with
+    response: Option<String>,
+    /// Message which this one is the answer to
+    answers: Option<String>,
+    fields: Vec<FieldDoc>,
+    raw_example: String,
+    cobs_example: String,
+}
+
+#[derive(Serialize)]
+struct FieldDoc {
+    offset: String,
+    size: String,
+    name: String,
+    wire_type: String,
+    /// Unit, scale and range of the value, if the protocol gives them
+    value: String,
+    description: String,
+}
+
+fn document_message(protocol: &ProtocolFile, grp: &CommandGroup, cmd: &Command) -> MessageDoc {
+    let response = protocol
+        .groups
+        .iter()
+        .flat_map(|grp| grp.commands.iter())
+        .find(|other| other.associated_request.as_ref() == Some(&cmd.name))
+        .map(|other| other.name.clone())
+        .or_else(|| cmd.acknowledged.then(|| GENERIC_RESPONSE.to_owned()));
+    let direction = match cmd.direction {
+        Direction::HostToDevice => "Host to device",
+        Direction::DeviceToHost => "Device to host",
+        Direction::Both => "Both",
+    };
+
+    let mut fields = vec![
+        FieldDoc {
+            offset: "0".to_owned(),
+            size: "1".to_owned(),
+            name: "Group".to_owned(),
+            wire_type: "`u8`".to_owned(),
+            value: format!("`{:#04x}`", grp.number),
+            description: String::new(),
+        },
+        FieldDoc {
+            offset: "1".to_owned(),
+            size: "1".to_owned(),
+            name: "Command".to_owned(),
+            wire_type: "`u8`".to_owned(),
+            value: format!("`{:#04x}`", cmd.number),
+            description: String::new(),
+        },
+    ];
+    let mut example = vec![grp.number, cmd.number];
+    let mut offset = Some(2);
+    for param in &cmd.parameters {
+        let param_type = ParamType::parse(&param.param_type, &protocol.types).unwrap();
+        let size = param_type.wire_size();
+        fields.push(FieldDoc {
+            offset: offset.map_or("variable".to_owned(), |offset| offset.to_string()),
+            size: size.map_or("1 + length".to_owned(), |size| size.to_string()),
+            name: param.name.clone(),
+            wire_type: param_type.describe(param.max_length),
+            value: describe_value(param),
+            description: param.description.clone(),
+        });
+        // Numbers are at their minimum so the device accepts the example. Length-prefixed
+        // values are empty so only their length byte is sent.
+        match (&param_type, param.min.and(param.raw_range(&param_type))) {
+            (ParamType::Scalar(ty), Some((min, _))) => example.extend(scalar_bytes(ty, min)),
+            _ => example.resize(example.len() + size.unwrap_or(1), 0),
+        }
+        offset = offset.zip(size).map(|(offset, size)| offset + size);
+    }
+
+    // Sent as the host's first frame
+    let mut frame = vec![1];
+    frame.extend_from_slice(&example);
+    frame.extend_from_slice(&crc16(&frame).to_be_bytes());
+    let mut cobs_example = cobs::encode_vec(&frame);
+    cobs_example.push(0x00);
+
+    MessageDoc {
+        name: cmd.name.clone(),
+        description: cmd.description.clone(),
+        group: grp.number,
+        number: cmd.number,
+        direction,
+        response,
+        answers: cmd.associated_request.clone(),
+        fields,
+        raw_example: to_hex(&example),
+        cobs_example: to_hex(&cobs_example),
+    }
+}
+
+/// `value` of one of the [crate::spec::SCALAR_TYPES] as it's carried on the wire
+fn scalar_bytes(ty: &str, value: f64) -> Vec<u8> {
+    match ty {
+        "u8" => (value as u8).to_be_bytes().to_vec(),
+        "i8" => (value as i8).to_be_bytes().to_vec(),
+        "u16" => (value as u16).to_be_bytes().to_vec(),
+        "i16" => (value as i16).to_be_bytes().to_vec(),
+        "u32" => (value as u32).to_be_bytes().to_vec(),
+        "i32" => (value as i32).to_be_bytes().to_vec(),
+        _ => (value as f32).to_be_bytes().to_vec(),
+    }
+}
+
+/// Renders the Markdown reference documentation for the protocol
+pub fn generate_docs(protocol: &ProtocolFile) -> Result<String, crate::Error> {
+    let groups = protocol
+        .groups
+        .iter()
+        .map(|grp| GroupDoc {
+            name: grp.group.clone(),
+            number: grp.number,
+            messages: grp
+                .commands
+                .iter()
+                .map(|cmd| document_message(protocol, grp, cmd))
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut env = minijinja::Environment::new();
+    env.add_template("docs", DOCS_TMPL)?;
+    env.add_filter("hex", |value: u32| format!("{value:#04x}"));
+    let rendered = env.get_template("docs")?.render(context! {
+        protocol => protocol,
+        groups => groups,
+    })?;
+    Ok(rendered)
+}
+
+/// Describes what the raw value of a parameter means, e.g. "× 0.1 percent, 0 to 100"
+fn describe_value(param: &Parameter) -> String {
+    let mut parts = Vec::new();
+    match (&param.unit, param.scale) {
+        (Some(unit), Some(scale)) => parts.push(format!("× {scale} {unit}")),
+        (Some(unit), None) => parts.push(unit.clone()),
+        _ => {}
+    }
+    match (param.min, param.max) {
+        (Some(min), Some(max)) => parts.push(format!("{min} to {max}")),
+        (Some(min), None) => parts.push(format!("at least {min}")),
+        (None, Some(max)) => parts.push(format!("at most {max}")),
+        (None, None) => {}
+    }
+    parts.join(", ")
+}
+
+/// CRC-16/CCITT-FALSE, the same as the generated modules' `crc16`
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Wraps the rendered Markdown documentation into a standalone HTML page
+pub fn markdown_to_html(title: &str, markdown: &str) -> String {
+    let parser = pulldown_cmark::Parser::new_ext(markdown, pulldown_cmark::Options::ENABLE_TABLES);
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, parser);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
@@ -0,0 +1,104 @@
+//! Generates code and documentation from the USB protocol definition in
+//! `usb-protocol/protocol.json5`. This is the core of `protocol-generator`, and is also called
+//! from `atmosensor-client`'s build script so its protocol module can't go stale.
+
+mod check;
+mod diff;
+mod dissector;
+mod docs;
+mod rust_module;
+mod spec;
+mod templates;
+mod vectors;
+mod version;
+
+pub use check::check_protocol;
+pub use diff::{check_version_bump, diff_protocols, Change, ChangeKind};
+pub use dissector::generate_dissector;
+pub use docs::{generate_docs, markdown_to_html};
+pub use rust_module::{format_rust_source, generate_module, Target};
+pub use spec::*;
+pub use templates::render_templates;
+pub use vectors::{load_vectors, TestVector};
+pub use version::Version;
+
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(json5::Error),
+    /// The protocol parsed but `check_protocol` found these problems with it
+    Invalid(Vec<String>),
+    Template(minijinja::Error),
+    /// `rustfmt` could not be run or rejected the generated source
+    Rustfmt(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Parse(err) => write!(f, "{err}"),
+            Error::Invalid(problems) => {
+                write!(f, "found {} problem(s) in the protocol", problems.len())
+            }
+            Error::Template(err) => write!(f, "failed to render template: {err}"),
+            Error::Rustfmt(reason) => write!(f, "failed to format generated code: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<json5::Error> for Error {
+    fn from(err: json5::Error) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(err: minijinja::Error) -> Self {
+        Error::Template(err)
+    }
+}
+
+/// Reads and checks a protocol definition. The problems in an [Error::Invalid] are prefixed
+/// with their location in the protocol but not with `path`.
+pub fn load_protocol(path: &Path) -> Result<ProtocolFile, Error> {
+    let protocol = std::fs::read_to_string(path)?;
+    let protocol = json5::from_str::<ProtocolFile>(&protocol)?;
+    let problems = check_protocol(&protocol);
+    if !problems.is_empty() {
+        return Err(Error::Invalid(problems));
+    }
+    Ok(protocol)
+}
+
+/// Reads an earlier revision of the protocol to compare the current one with, without checking
+/// it. Revisions from before messages had a `direction` are read as if every message went both
+/// ways, as they could then.
+pub fn load_old_protocol(path: &Path) -> Result<ProtocolFile, Error> {
+    let protocol = std::fs::read_to_string(path)?;
+    let mut protocol = json5::from_str::<serde_json::Value>(&protocol)?;
+    let commands = protocol
+        .get_mut("groups")
+        .and_then(|groups| groups.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.get_mut("commands")?.as_array_mut())
+        .flatten()
+        .filter_map(|command| command.as_object_mut());
+    for command in commands {
+        command
+            .entry("direction")
+            .or_insert_with(|| serde_json::json!("both"));
+    }
+    Ok(json5::from_str(&protocol.to_string())?)
+}
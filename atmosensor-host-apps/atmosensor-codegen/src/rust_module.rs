@@ -0,0 +1,147 @@
+use minijinja::context;
+use std::{io::Write, process::Stdio};
+
+use crate::spec::{spec_hash, ParamType, Parameter, ProtocolFile, GENERIC_RESPONSE};
+use crate::templates::{add_filters, float_literal};
+
+const MESSAGE_STRUCT_TMPL: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/message_struct.rs.j2"
+));
+const MODULE_TMPL: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/module.rs.j2"));
+const FIRMWARE_MESSAGE_STRUCT_TMPL: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/firmware_message_struct.rs.j2"
+));
+const TYPE_DEFINITION_TMPL: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/type_definition.rs.j2"
+));
+const FIRMWARE_MODULE_TMPL: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/firmware_module.rs.j2"
+));
+/// The client's copy of [crate::Version], so that both judge compatibility the same way
+const VERSION_SOURCE: &str = include_str!("version.rs");
+
+/// Consumer of the protocol which a Rust module is generated for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// std module for atmosensor-client, messages are encoded into a `Vec<u8>`
+    Client,
+    /// no_std, allocation-free module for atmosensor-fw
+    Firmware,
+}
+
+impl Target {
+    fn templates(self) -> (&'static str, &'static str) {
+        match self {
+            Target::Client => (MESSAGE_STRUCT_TMPL, MODULE_TMPL),
+            Target::Firmware => (FIRMWARE_MESSAGE_STRUCT_TMPL, FIRMWARE_MODULE_TMPL),
+        }
+    }
+}
+
+impl ParamType {
+    pub(crate) fn rust_type(&self, target: Target, max_length: Option<u8>) -> String {
+        let max_length = max_length.unwrap_or_default();
+        match (self, target) {
+            (ParamType::Bool, _) => "bool".to_owned(),
+            (ParamType::Scalar(ty), _) => (*ty).to_owned(),
+            (ParamType::ByteArray(len), _) => format!("[u8; {len}]"),
+            (ParamType::Named { name, .. }, _) => name.clone(),
+            (ParamType::String, Target::Client) => "String".to_owned(),
+            (ParamType::Bytes, Target::Client) => "Vec<u8>".to_owned(),
+            (ParamType::String, Target::Firmware) => format!("heapless::String<{max_length}>"),
+            (ParamType::Bytes, Target::Firmware) => format!("heapless::Vec<u8, {max_length}>"),
+        }
+    }
+}
+
+/// Renders the Rust module for `target`. The source is not formatted, pass it through
+/// [format_rust_source] before checking it in.
+pub fn generate_module(protocol: &ProtocolFile, target: Target) -> Result<String, crate::Error> {
+    let (message_tmpl, module_tmpl) = target.templates();
+    let mut env = minijinja::Environment::new();
+    env.add_template("message", message_tmpl)?;
+    env.add_template("module", module_tmpl)?;
+    env.add_template("type", TYPE_DEFINITION_TMPL)?;
+    add_filters(&mut env, &protocol.types);
+    let types = protocol.types.clone();
+    env.add_filter("rust_type", move |v: minijinja::value::Value| {
+        let param = serde_json::from_value::<Parameter>(serde_json::to_value(v).unwrap()).unwrap();
+        let param_type = ParamType::parse(&param.param_type, &types).unwrap();
+        minijinja::value::Value::from(param_type.rust_type(target, param.max_length))
+    });
+
+    let types = protocol.types.clone();
+    env.add_filter("raw_range", move |v: minijinja::value::Value| {
+        let param = serde_json::from_value::<Parameter>(serde_json::to_value(v).unwrap()).unwrap();
+        let param_type = ParamType::parse(&param.param_type, &types).unwrap();
+        let range = param.raw_range(&param_type).map(|(min, max)| {
+            [min, max].map(|bound| match param_type {
+                ParamType::Scalar("f32") => float_literal(bound),
+                _ => (bound as i64).to_string(),
+            })
+        });
+        minijinja::value::Value::from_serializable(&range)
+    });
+
+    let tmpl = env.get_template("type")?;
+    let type_definitions = protocol
+        .types
+        .iter()
+        .map(|ty| {
+            // Serde support is only generated for the client, behind its `serde` feature
+            tmpl.render(context! { definition => ty, client => target == Target::Client })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tmpl = env.get_template("message")?;
+    let command_definitions = protocol
+        .groups
+        .iter()
+        .flat_map(|grp| {
+            grp.commands.iter().map(|cmd| {
+                // Messages with units or ranges get a checked constructor and accessors
+                let checked = cmd.parameters.iter().any(|param| {
+                    param.unit.is_some() || param.min.is_some() || param.max.is_some()
+                });
+                tmpl.render(context! { group => grp.number, command => cmd, checked => checked })
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tmpl = env.get_template("module")?;
+    let module_definition = tmpl.render(context! {
+        protocol => protocol,
+        spec_hash => format!("{:#010x}", spec_hash(protocol)),
+        generic_response => GENERIC_RESPONSE,
+        version_source => VERSION_SOURCE,
+        types => type_definitions,
+        commands => command_definitions,
+    })?;
+    Ok(module_definition)
+}
+
+/// Formats `source` with `rustfmt`, which has to be on the `PATH`
+pub fn format_rust_source(source: &str) -> Result<String, crate::Error> {
+    let mut process = std::process::Command::new("rustfmt")
+        .arg("--emit=stdout")
+        .arg("--edition=2021")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| crate::Error::Rustfmt(format!("could not run rustfmt: {err}")))?;
+    process.stdin.take().unwrap().write_all(source.as_bytes())?;
+
+    let output = process.wait_with_output()?;
+    if !output.status.success() {
+        return Err(crate::Error::Rustfmt(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| crate::Error::Rustfmt("rustfmt emitted invalid UTF-8".to_owned()))
+}
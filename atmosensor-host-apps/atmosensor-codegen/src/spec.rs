@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+/// The contents of `protocol.json5`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProtocolFile {
+    pub version: String,
+    #[serde(default)]
+    pub types: Vec<TypeDefinition>,
+    pub groups: Vec<CommandGroup>,
+}
+
+/// A named type which parameters can use in place of a raw integer
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub kind: TypeKind,
+    /// Unsigned integer type the value is carried as on the wire
+    pub repr: String,
+    pub description: String,
+    pub values: Vec<TypeValue>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypeKind {
+    /// Exactly one of the listed values
+    Enum,
+    /// Any combination of the listed bits
+    Bitflags,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TypeValue {
+    pub name: String,
+    pub value: u32,
+    pub description: String,
+}
+
+pub(crate) const TYPE_REPRS: [&str; 3] = ["u8", "u16", "u32"];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommandGroup {
+    pub group: String,
+    pub number: u8,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Command {
+    pub name: String,
+    pub associated_request: Option<String>,
+    /// Whether the device answers this command with a [GENERIC_RESPONSE]
+    #[serde(default)]
+    pub acknowledged: bool,
+    pub direction: Direction,
+    pub number: u8,
+    pub description: String,
+    pub parameters: Vec<Parameter>,
+}
+
+/// Which side of the link sends a message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    HostToDevice,
+    DeviceToHost,
+    Both,
+}
+
+impl Direction {
+    /// Whether the host may send this message, so it's part of `HostMessage`
+    pub fn from_host(self) -> bool {
+        self != Direction::DeviceToHost
+    }
+
+    /// Whether the device may send this message, so it's part of `DeviceMessage`
+    pub fn from_device(self) -> bool {
+        self != Direction::HostToDevice
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::HostToDevice => write!(f, "host_to_device"),
+            Direction::DeviceToHost => write!(f, "device_to_host"),
+            Direction::Both => write!(f, "both"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    /// One of the [ParamType] spellings
+    #[serde(rename = "type")]
+    pub param_type: String,
+    /// Upper bound on the length of `string` and `bytes` parameters, used to size the
+    /// firmware's buffers
+    pub max_length: Option<u8>,
+    pub description: String,
+    /// Physical unit of a numeric parameter once multiplied by its `scale`, spelled so that it
+    /// can end an identifier (`percent`, `celsius`, ...)
+    pub unit: Option<String>,
+    /// What one count of the raw value is worth in `unit`, 1 if not given
+    pub scale: Option<f64>,
+    /// Smallest allowed value in `unit`
+    pub min: Option<f64>,
+    /// Largest allowed value in `unit`
+    pub max: Option<f64>,
+}
+
+impl Parameter {
+    /// Allowed raw values as they're carried on the wire, `None` if the protocol doesn't
+    /// restrict them beyond their type. Bounds of integers are rounded inwards.
+    pub(crate) fn raw_range(&self, param_type: &ParamType) -> Option<(f64, f64)> {
+        if self.min.is_none() && self.max.is_none() {
+            return None;
+        }
+        let ParamType::Scalar(ty) = param_type else {
+            return None;
+        };
+        let (type_min, type_max) = scalar_range(ty);
+        let scale = self
+            .scale
+            .filter(|scale| scale.is_finite() && *scale > 0.0)
+            .unwrap_or(1.0);
+        let mut min = self.min.map_or(type_min, |min| min / scale);
+        let mut max = self.max.map_or(type_max, |max| max / scale);
+        if *ty != "f32" {
+            // Tolerate the error of dividing by a scale like 0.1 before rounding
+            min = (min - 1e-9).ceil();
+            max = (max + 1e-9).floor();
+        }
+        Some((min.max(type_min), max.min(type_max)))
+    }
+}
+
+/// Smallest and largest values of one of the [SCALAR_TYPES]
+pub(crate) fn scalar_range(ty: &str) -> (f64, f64) {
+    match ty {
+        "u8" => (u8::MIN.into(), u8::MAX.into()),
+        "i8" => (i8::MIN.into(), i8::MAX.into()),
+        "u16" => (u16::MIN.into(), u16::MAX.into()),
+        "i16" => (i16::MIN.into(), i16::MAX.into()),
+        "u32" => (u32::MIN.into(), u32::MAX.into()),
+        "i32" => (i32::MIN.into(), i32::MAX.into()),
+        _ => (f32::MIN.into(), f32::MAX.into()),
+    }
+}
+
+/// Wire representation of a parameter. Multi-byte values are big-endian and variable
+/// length values are prefixed with their length in bytes as a `u8`.
+#[derive(Clone, PartialEq)]
+pub(crate) enum ParamType {
+    /// A single byte which is zero for false
+    Bool,
+    /// A fixed-width integer or float, spelled as the Rust type (`u8`, `i16`, `f32`, ...)
+    Scalar(&'static str),
+    /// A fixed number of raw bytes, spelled `u8[N]`
+    ByteArray(usize),
+    /// Length-prefixed UTF-8 text
+    String,
+    /// Length-prefixed raw bytes
+    Bytes,
+    /// One of the protocol's `types`, spelled by its name and carried as its `repr`
+    Named { name: String, repr: &'static str },
+}
+
+pub(crate) const SCALAR_TYPES: [&str; 7] = ["u8", "i8", "u16", "i16", "u32", "i32", "f32"];
+
+impl ParamType {
+    pub(crate) fn parse(s: &str, types: &[TypeDefinition]) -> Result<Self, String> {
+        if let Some(scalar) = SCALAR_TYPES.iter().find(|ty| **ty == s) {
+            return Ok(ParamType::Scalar(scalar));
+        }
+        if let Some(ty) = types.iter().find(|ty| ty.name == s) {
+            let repr = TYPE_REPRS
+                .iter()
+                .find(|repr| **repr == ty.repr)
+                .ok_or_else(|| format!("type `{}` has unsupported repr `{}`", ty.name, ty.repr))?;
+            return Ok(ParamType::Named {
+                name: ty.name.clone(),
+                repr,
+            });
+        }
+        match s {
+            "bool" => Ok(ParamType::Bool),
+            "string" => Ok(ParamType::String),
+            "bytes" => Ok(ParamType::Bytes),
+            _ => s
+                .strip_prefix("u8[")
+                .and_then(|len| len.strip_suffix(']'))
+                .and_then(|len| len.parse().ok())
+                .map(ParamType::ByteArray)
+                .ok_or_else(|| format!("unknown parameter type `{s}`")),
+        }
+    }
+
+    /// Name the templates use to pick how a parameter is read and written
+    pub(crate) fn wire_kind(&self) -> &'static str {
+        match self {
+            ParamType::Bool => "bool",
+            ParamType::Scalar(_) => "scalar",
+            ParamType::ByteArray(_) => "array",
+            ParamType::String => "string",
+            ParamType::Bytes => "bytes",
+            ParamType::Named { .. } => "named",
+        }
+    }
+
+    /// The scalar type which a value is carried as on the wire
+    pub(crate) fn wire_repr(&self) -> Option<&'static str> {
+        match self {
+            ParamType::Scalar(ty) => Some(ty),
+            ParamType::Named { repr, .. } => Some(repr),
+            _ => None,
+        }
+    }
+
+    /// Number of bytes the parameter takes on the wire, `None` if it's length-prefixed
+    pub(crate) fn wire_size(&self) -> Option<usize> {
+        match self {
+            ParamType::Bool => Some(1),
+            ParamType::Scalar(ty) | ParamType::Named { repr: ty, .. } => Some(match *ty {
+                "u8" | "i8" => 1,
+                "u16" | "i16" => 2,
+                _ => 4,
+            }),
+            ParamType::ByteArray(len) => Some(*len),
+            ParamType::String | ParamType::Bytes => None,
+        }
+    }
+}
+
+/// Shared acknowledgement which answers many requests, so it has no `associated_request`
+pub const GENERIC_RESPONSE: &str = "GenericResponse";
+
+/// Answers any request the device failed to carry out, so it has no `associated_request`
+pub const ERROR_RESPONSE: &str = "ErrorResponse";
+
+/// 32-bit FNV-1a hash of the protocol, which peers exchange to tell whether they were built from
+/// exactly the same definition. Formatting and comments of `protocol.json5` don't affect it.
+pub fn spec_hash(protocol: &ProtocolFile) -> u32 {
+    let canonical = serde_json::to_vec(protocol).expect("the protocol serializes to JSON");
+    canonical.iter().fold(0x811c9dc5, |hash: u32, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
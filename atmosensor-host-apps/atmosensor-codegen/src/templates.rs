@@ -0,0 +1,124 @@
+use convert_case::{Case, Casing};
+use minijinja::{context, Environment, ErrorKind};
+use std::path::Path;
+
+use crate::spec::{
+    spec_hash, ParamType, ProtocolFile, TypeDefinition, ERROR_RESPONSE, GENERIC_RESPONSE,
+};
+
+impl ParamType {
+    /// C type of a parameter, or of the elements of `u8[N]`, `string` and `bytes` parameters
+    /// which templates size with `wire_size` or `max_length`
+    pub(crate) fn c_type(&self) -> String {
+        match self {
+            ParamType::Bool => "bool".to_owned(),
+            ParamType::Scalar("f32") => "float".to_owned(),
+            ParamType::Scalar(ty) => match ty.strip_prefix('u') {
+                Some(bits) => format!("uint{bits}_t"),
+                None => format!("int{}_t", &ty[1..]),
+            },
+            ParamType::ByteArray(_) | ParamType::Bytes => "uint8_t".to_owned(),
+            ParamType::String => "char".to_owned(),
+            ParamType::Named { name, .. } => name.clone(),
+        }
+    }
+}
+
+/// Adds the filters which the generator's own templates and custom templates share. Casing
+/// filters take names as `protocol.json5` spells them, type filters take a parameter's `type`.
+pub(crate) fn add_filters(env: &mut Environment, types: &[TypeDefinition]) {
+    env.add_filter("param_case", |value: String| value.to_case(Case::Snake));
+    env.add_filter("const_case", |value: String| {
+        value.to_case(Case::UpperSnake)
+    });
+    env.add_filter("pascal_case", |value: String| value.to_case(Case::Pascal));
+    env.add_filter("camel_case", |value: String| value.to_case(Case::Camel));
+    env.add_filter("kebab_case", |value: String| value.to_case(Case::Kebab));
+    env.add_filter("hex", |value: u32| format!("{value:#04x}"));
+    env.add_filter("float_literal", float_literal);
+    env.add_filter("str_literal", |value: String| format!("{value:?}"));
+
+    let parse = {
+        let types = types.to_vec();
+        move |ty: &str| {
+            ParamType::parse(ty, &types)
+                .map_err(|err| minijinja::Error::new(ErrorKind::InvalidOperation, err))
+        }
+    };
+    let type_filter = |map: fn(ParamType) -> minijinja::value::Value| {
+        let parse = parse.clone();
+        move |ty: String| parse(&ty).map(map)
+    };
+    env.add_filter(
+        "wire_kind",
+        type_filter(|ty| minijinja::value::Value::from(ty.wire_kind())),
+    );
+    env.add_filter(
+        "wire_repr",
+        type_filter(|ty| minijinja::value::Value::from_serializable(&ty.wire_repr())),
+    );
+    env.add_filter(
+        "wire_size",
+        type_filter(|ty| minijinja::value::Value::from_serializable(&ty.wire_size())),
+    );
+    env.add_filter(
+        "c_type",
+        type_filter(|ty| minijinja::value::Value::from(ty.c_type())),
+    );
+}
+
+/// Spells a value so that Rust and C read it as a float, even if it's a whole number
+pub(crate) fn float_literal(value: f64) -> String {
+    format!("{value:?}")
+}
+
+/// Renders templates which aren't built into the generator, e.g. to generate code for another
+/// language. `names` are paths relative to `dir`, and every file below `dir` is loaded so that
+/// the templates can include and extend each other. The templates get the same context as the
+/// built-in module template and the filters of [add_filters].
+pub fn render_templates(
+    protocol: &ProtocolFile,
+    dir: &Path,
+    names: &[String],
+) -> Result<Vec<String>, crate::Error> {
+    let mut sources = Vec::new();
+    read_templates(dir, "", &mut sources)?;
+    let mut env = Environment::new();
+    for (name, source) in &sources {
+        env.add_template(name, source)?;
+    }
+    add_filters(&mut env, &protocol.types);
+
+    let context = context! {
+        protocol => protocol,
+        spec_hash => format!("{:#010x}", spec_hash(protocol)),
+        generic_response => GENERIC_RESPONSE,
+        error_response => ERROR_RESPONSE,
+    };
+    names
+        .iter()
+        .map(|name| Ok(env.get_template(name)?.render(&context)?))
+        .collect()
+}
+
+/// Reads every file below `dir` as a template named by its path relative to `dir`, with `/`
+/// separating its components
+fn read_templates(
+    dir: &Path,
+    prefix: &str,
+    out: &mut Vec<(String, String)>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            read_templates(&entry.path(), &format!("{name}/"), out)?;
+        } else {
+            let source = std::fs::read_to_string(entry.path()).map_err(|err| {
+                std::io::Error::new(err.kind(), format!("{}: {err}", entry.path().display()))
+            })?;
+            out.push((name, source));
+        }
+    }
+    Ok(())
+}
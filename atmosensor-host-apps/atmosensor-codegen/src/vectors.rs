@@ -0,0 +1,49 @@
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+
+/// The contents of `vectors.json5`
+#[derive(Clone, Deserialize)]
+struct VectorsFile {
+    vectors: Vec<TestVector>,
+}
+
+/// A message and the exact bytes it's sent as, which the client's and the firmware's protocol
+/// code are tested against
+#[derive(Clone, Debug, Deserialize)]
+pub struct TestVector {
+    /// Name of the message as `protocol.json5` spells it
+    pub message: String,
+    /// Parameters in the form `HostMessage::from_args` and `DeviceMessage::from_args` take
+    pub args: Vec<String>,
+    /// Sequence id of `frame`
+    pub sequence: u8,
+    /// The encoded message, group and number first
+    #[serde(deserialize_with = "hex")]
+    pub raw: Vec<u8>,
+    /// `sequence`, `raw` and their CRC, COBS-encoded and terminated by 0x00
+    #[serde(deserialize_with = "hex")]
+    pub frame: Vec<u8>,
+}
+
+fn hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    if hex.len() % 2 != 0 {
+        return Err(serde::de::Error::custom(format!(
+            "odd number of hex digits in {hex:?}"
+        )));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid hex {hex:?}")))
+        })
+        .collect()
+}
+
+/// Reads the golden test vectors, e.g. `usb-protocol/vectors.json5`
+pub fn load_vectors(path: &Path) -> Result<Vec<TestVector>, crate::Error> {
+    let vectors = std::fs::read_to_string(path)?;
+    Ok(json5::from_str::<VectorsFile>(&vectors)?.vectors)
+}
@@ -0,0 +1,31 @@
+// This file is also copied into the client's generated module, so it only uses std.
+
+/// A `major.minor.patch` version of the protocol
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(pub u64, pub u64, pub u64);
+
+impl std::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let mut parts = version.split('.').map(|part| part.parse::<u64>());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => {
+                Ok(Version(major, minor, patch))
+            }
+            _ => Err(format!("`{version}` is not a major.minor.patch version")),
+        }
+    }
+}
+
+impl Version {
+    /// Whether peers of the two versions are expected to understand each other, following
+    /// Cargo's reading of semver where `0.x` releases break by bumping `x`
+    pub fn compatible_with(self, other: Version) -> bool {
+        match (self, other) {
+            (Version(0, 0, a), Version(0, 0, b)) => a == b,
+            (Version(0, a, _), Version(0, b, _)) => a == b,
+            (Version(a, _, _), Version(b, _, _)) => a == b,
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Checks that `check_protocol` reports each kind of mistake, one lint per test, on a small
+//! protocol which every test breaks in one place.
+
+mod common;
+
+use atmosensor_codegen::{check_protocol, Direction, ProtocolFile, TypeValue};
+use common::{base, parameter};
+
+/// The errors `check_protocol` reports for `change(base)`
+fn errors(change: impl FnOnce(&mut ProtocolFile)) -> Vec<String> {
+    let mut protocol = base();
+    change(&mut protocol);
+    check_protocol(&protocol)
+}
+
+/// Asserts that `change(base)` is reported once, with an error containing `expected`
+fn assert_reported(change: impl FnOnce(&mut ProtocolFile), expected: &str) {
+    let errors = errors(change);
+    assert!(
+        errors.len() == 1 && errors[0].contains(expected),
+        "expected one error containing {expected:?}, got {errors:?}"
+    );
+}
+
+#[test]
+fn base_protocol_passes() {
+    assert_eq!(errors(|_| {}), Vec::<String>::new());
+}
+
+#[test]
+fn duplicate_type() {
+    assert_reported(
+        |protocol| protocol.types.push(protocol.types[0].clone()),
+        "types.Mode: type is defined more than once",
+    );
+}
+
+#[test]
+fn unsupported_repr() {
+    // Parameters of the type can't be generated either
+    assert_eq!(
+        errors(|protocol| protocol.types[0].repr = "i8".to_owned()),
+        [
+            r#"types.Mode: unsupported repr `i8`, expected one of ["u8", "u16", "u32"]"#,
+            "Sensor.SetMode.Mode: type `Mode` has unsupported repr `i8`",
+        ]
+    );
+}
+
+#[test]
+fn type_without_values() {
+    assert_reported(
+        |protocol| protocol.types[0].values.clear(),
+        "types.Mode: type has no values",
+    );
+}
+
+#[test]
+fn duplicate_type_value() {
+    assert_reported(
+        |protocol| protocol.types[0].values[1].value = 0,
+        "values `Off` and `On` are both 0",
+    );
+}
+
+#[test]
+fn type_value_names_collide() {
+    // Bitflags constants are UPPER_SNAKE_CASE, so names differing only in case collide
+    assert_reported(
+        |protocol| {
+            protocol.types[1].values.push(TypeValue {
+                name: "HighCO2".to_owned(),
+                value: 4,
+                description: String::new(),
+            })
+        },
+        "values `HighCo2` and `HighCO2` both generate `HIGH_CO_2`",
+    );
+}
+
+#[test]
+fn type_value_out_of_range() {
+    assert_reported(
+        |protocol| protocol.types[0].values[1].value = 256,
+        "value `On` (256) does not fit in a u8",
+    );
+}
+
+#[test]
+fn duplicate_group() {
+    assert_reported(
+        |protocol| protocol.groups[0].group = "Sensor".to_owned(),
+        "Sensor: group is defined more than once",
+    );
+}
+
+#[test]
+fn duplicate_id() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].number = 1,
+        "Sensor.LevelResponse: (group, number) (0x01, 0x01) is already used by Sensor.RequestLevel",
+    );
+}
+
+#[test]
+fn duplicate_command_name() {
+    let errors = errors(|protocol| {
+        let mut command = protocol.groups[1].commands[1].clone();
+        command.number = 3;
+        protocol.groups[1].commands.push(command);
+    });
+    assert!(
+        errors.contains(&"Sensor.RequestLevel: command name is used more than once".to_owned()),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn command_named_like_a_type() {
+    assert_reported(
+        |protocol| {
+            let mut command = protocol.groups[1].commands[1].clone();
+            command.name = "Mode".to_owned();
+            command.number = 3;
+            protocol.groups[1].commands.push(command);
+        },
+        "Sensor.Mode: command has the same name as a type",
+    );
+}
+
+#[test]
+fn handler_methods_collide() {
+    assert_reported(
+        |protocol| {
+            let mut command = protocol.groups[1].commands[1].clone();
+            command.name = "RequestLEVEL".to_owned();
+            command.number = 3;
+            protocol.groups[1].commands.push(command);
+        },
+        "both become the Handler method `request_level`",
+    );
+    assert_reported(
+        |protocol| protocol.groups[1].commands[0].name = "NotSupported".to_owned(),
+        "Handler method `not_supported` clashes with a generated method",
+    );
+}
+
+#[test]
+fn associated_request_is_not_a_command() {
+    assert_reported(
+        |protocol| {
+            protocol.groups[1].commands[2].associated_request = Some("RequestLvl".to_owned())
+        },
+        "associated_request `RequestLvl` is not a command",
+    );
+}
+
+#[test]
+fn associated_request_is_acknowledged() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].associated_request = Some("SetMode".to_owned()),
+        "associated_request `SetMode` is already acknowledged with GenericResponse",
+    );
+}
+
+#[test]
+fn associated_request_is_never_sent_by_the_host() {
+    assert_reported(
+        |protocol| {
+            protocol.groups[1].commands[2].associated_request = Some("GenericResponse".to_owned())
+        },
+        "associated_request `GenericResponse` is never sent by the host",
+    );
+}
+
+#[test]
+fn request_answered_twice() {
+    assert_reported(
+        |protocol| {
+            let mut command = protocol.groups[1].commands[2].clone();
+            command.name = "OtherLevelResponse".to_owned();
+            command.number = 3;
+            protocol.groups[1].commands.push(command);
+        },
+        "`RequestLevel` is already answered by `LevelResponse`",
+    );
+}
+
+#[test]
+fn orphan_response() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].associated_request = None,
+        "Sensor.LevelResponse: response has no associated_request",
+    );
+}
+
+#[test]
+fn response_never_sent_by_the_device() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].direction = Direction::HostToDevice,
+        "Sensor.LevelResponse: response is never sent by the device",
+    );
+    assert_reported(
+        |protocol| protocol.groups[0].commands[0].direction = Direction::HostToDevice,
+        "General.GenericResponse: is never sent by the device",
+    );
+}
+
+#[test]
+fn acknowledged_but_never_sent_by_the_host() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[0].direction = Direction::DeviceToHost,
+        "Sensor.SetMode: acknowledged but never sent by the host",
+    );
+}
+
+#[test]
+fn acknowledged_without_generic_response() {
+    assert_reported(
+        |protocol| {
+            protocol.groups.remove(0);
+        },
+        "Sensor.SetMode: acknowledged but there is no GenericResponse command",
+    );
+}
+
+#[test]
+fn parameter_names_collide() {
+    assert_reported(
+        |protocol| {
+            protocol.groups[1].commands[0]
+                .parameters
+                .push(parameter("MODE", "u8"))
+        },
+        "Sensor.SetMode.MODE: collides with parameter `Mode`, both become `mode`",
+    );
+}
+
+#[test]
+fn unknown_parameter_type() {
+    let errors = errors(|protocol| {
+        protocol.groups[1].commands[0].parameters[0].param_type = "Modes".to_owned()
+    });
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].starts_with("Sensor.SetMode.Mode: "), "{errors:?}");
+    assert!(errors[0].contains("Modes"), "{errors:?}");
+}
+
+#[test]
+fn string_without_max_length() {
+    assert_reported(
+        |protocol| {
+            protocol.groups[1].commands[0]
+                .parameters
+                .push(parameter("Name", "string"))
+        },
+        "Sensor.SetMode.Name: `string` parameters require a `max_length`",
+    );
+}
+
+#[test]
+fn units_on_a_non_numeric_parameter() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[0].parameters[0].unit = Some("mode".to_owned()),
+        "only numeric parameters can have a unit, scale or range",
+    );
+}
+
+#[test]
+fn invalid_unit() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].parameters[0].unit = Some("Percent".to_owned()),
+        "unit `Percent` must be lowercase letters, digits and underscores",
+    );
+}
+
+#[test]
+fn scale_without_unit() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].parameters[0].unit = None,
+        "Sensor.LevelResponse.Level: a scale needs a unit",
+    );
+}
+
+#[test]
+fn non_positive_scale() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].parameters[0].scale = Some(0.0),
+        "scale 0 must be positive",
+    );
+}
+
+#[test]
+fn range_beyond_the_type() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].parameters[0].max = Some(7000.0),
+        "max 7000 is above what a `u16` can carry",
+    );
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].parameters[0].min = Some(-1.0),
+        "min -1 is below what a `u16` can carry",
+    );
+}
+
+#[test]
+fn min_above_max() {
+    assert_reported(
+        |protocol| protocol.groups[1].commands[2].parameters[0].min = Some(200.0),
+        "min 200 is above max 100",
+    );
+}
+
+#[test]
+fn no_raw_value_within_range() {
+    assert_reported(
+        |protocol| {
+            let level = &mut protocol.groups[1].commands[2].parameters[0];
+            level.min = Some(0.01);
+            level.max = Some(0.09);
+        },
+        "no raw value is within min and max",
+    );
+}
+
+#[test]
+fn accessor_clashes_with_a_generated_method() {
+    assert_reported(
+        |protocol| {
+            let level = &mut protocol.groups[1].commands[2].parameters[0];
+            level.name = "To".to_owned();
+            level.unit = Some("bytes".to_owned());
+        },
+        "accessor `to_bytes` clashes with a generated method",
+    );
+}
+
+#[test]
+fn accessors_collide() {
+    assert_reported(
+        |protocol| {
+            let parameters = &mut protocol.groups[1].commands[2].parameters;
+            parameters[0].unit = Some("raw_percent".to_owned());
+            let mut level_raw = parameter("LevelRaw", "u16");
+            level_raw.unit = Some("percent".to_owned());
+            parameters.push(level_raw);
+        },
+        "accessor `level_raw_percent` is also generated for `Level`",
+    );
+}
@@ -0,0 +1,47 @@
+//! Fails when a generated file which is checked in no longer matches `usb-protocol/protocol.json5`,
+//! run `atmosensor-tools/regenerate-protocol-mod.sh` to bring them back in line.
+
+use std::path::{Path, PathBuf};
+
+fn repo_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+fn assert_up_to_date(generated: &str, checked_in: &str) {
+    let contents = std::fs::read_to_string(repo_dir().join(checked_in)).unwrap();
+    assert!(
+        contents == generated,
+        "{checked_in} is out of date with the protocol"
+    );
+}
+
+#[test]
+fn checked_in_files_match_protocol() {
+    let protocol =
+        atmosensor_codegen::load_protocol(&repo_dir().join("usb-protocol/protocol.json5")).unwrap();
+
+    let firmware =
+        atmosensor_codegen::generate_module(&protocol, atmosensor_codegen::Target::Firmware)
+            .unwrap();
+    let firmware = atmosensor_codegen::format_rust_source(&firmware).unwrap();
+    assert_up_to_date(&firmware, "atmosensor-fw/src/protocol/autogen.rs");
+
+    let docs = atmosensor_codegen::generate_docs(&protocol).unwrap();
+    assert_up_to_date(&docs, "usb-protocol/protocol.md");
+
+    let dissector = atmosensor_codegen::generate_dissector(&protocol).unwrap();
+    assert_up_to_date(&dissector, "usb-protocol/atmosensor.lua");
+}
+
+/// The client's module isn't checked in, but `protocol-generator -t client` formats it and fails
+/// on anything rustfmt won't take, e.g. trailing whitespace left by a template
+#[test]
+fn client_module_formats() {
+    let protocol =
+        atmosensor_codegen::load_protocol(&repo_dir().join("usb-protocol/protocol.json5")).unwrap();
+    let client =
+        atmosensor_codegen::generate_module(&protocol, atmosensor_codegen::Target::Client).unwrap();
+    if let Err(err) = atmosensor_codegen::format_rust_source(&client) {
+        panic!("{err}");
+    }
+}
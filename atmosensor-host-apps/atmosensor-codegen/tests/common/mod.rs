@@ -0,0 +1,114 @@
+//! Fixtures shared by the tests of `check_protocol` and `diff_protocols`
+
+use atmosensor_codegen::{Parameter, ProtocolFile};
+
+/// A small protocol which passes every check, and which the tests change one thing about
+pub fn base() -> ProtocolFile {
+    json5::from_str(
+        r#"{
+            "version": "1.0.0",
+            "types": [
+                {
+                    "name": "Mode",
+                    "kind": "enum",
+                    "repr": "u8",
+                    "description": "",
+                    "values": [
+                        { "name": "Off", "value": 0, "description": "" },
+                        { "name": "On", "value": 1, "description": "" }
+                    ]
+                },
+                {
+                    "name": "Alarms",
+                    "kind": "bitflags",
+                    "repr": "u8",
+                    "description": "",
+                    "values": [
+                        { "name": "HighCo2", "value": 1, "description": "" },
+                        { "name": "LowBattery", "value": 2, "description": "" }
+                    ]
+                }
+            ],
+            "groups": [
+                {
+                    "group": "General",
+                    "number": 0,
+                    "commands": [
+                        {
+                            "name": "GenericResponse",
+                            "direction": "device_to_host",
+                            "number": 0,
+                            "description": "",
+                            "parameters": [
+                                { "name": "Successful", "type": "bool", "description": "" }
+                            ]
+                        }
+                    ]
+                },
+                {
+                    "group": "Sensor",
+                    "number": 1,
+                    "commands": [
+                        {
+                            "name": "SetMode",
+                            "direction": "host_to_device",
+                            "acknowledged": true,
+                            "number": 0,
+                            "description": "",
+                            "parameters": [
+                                { "name": "Mode", "type": "Mode", "description": "" },
+                                { "name": "Level", "type": "u8", "description": "" }
+                            ]
+                        },
+                        {
+                            "name": "RequestLevel",
+                            "direction": "host_to_device",
+                            "number": 1,
+                            "description": "",
+                            "parameters": []
+                        },
+                        {
+                            "name": "LevelResponse",
+                            "associated_request": "RequestLevel",
+                            "direction": "device_to_host",
+                            "number": 2,
+                            "description": "",
+                            "parameters": [
+                                {
+                                    "name": "Level",
+                                    "type": "u16",
+                                    "description": "",
+                                    "unit": "percent",
+                                    "scale": 0.1,
+                                    "max": 100
+                                },
+                                { "name": "Alarms", "type": "Alarms", "description": "" }
+                            ]
+                        },
+                        {
+                            "name": "Status",
+                            "direction": "device_to_host",
+                            "number": 4,
+                            "description": "",
+                            "parameters": []
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .unwrap()
+}
+
+pub fn parameter(name: &str, param_type: &str) -> Parameter {
+    Parameter {
+        name: name.to_owned(),
+        param_type: param_type.to_owned(),
+        max_length: None,
+        description: String::new(),
+        unit: None,
+        scale: None,
+        min: None,
+        max: None,
+    }
+}
@@ -0,0 +1,248 @@
+//! Checks how `diff_protocols` classifies each kind of change, and which version bumps
+//! `check_version_bump` accepts for them.
+
+mod common;
+
+use atmosensor_codegen::{
+    check_version_bump, diff_protocols, load_old_protocol, ChangeKind, Direction, ProtocolFile,
+    TypeValue,
+};
+use common::{base, parameter};
+
+/// The kinds of the changes from the base protocol to `change(base)`
+fn diff(change: impl FnOnce(&mut ProtocolFile)) -> Vec<ChangeKind> {
+    let mut new = base();
+    change(&mut new);
+    diff_protocols(&base(), &new)
+        .into_iter()
+        .map(|change| change.kind)
+        .collect()
+}
+
+#[test]
+fn unchanged_protocol_has_no_changes() {
+    assert_eq!(diff(|_| {}), []);
+}
+
+#[test]
+fn descriptions_are_ignored() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[0].description = "Sets the mode".to_owned()),
+        []
+    );
+}
+
+#[test]
+fn renumbered_command_is_breaking() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[3].number = 5),
+        [ChangeKind::Breaking]
+    );
+    // Every command of a renumbered group moves, and its new number shows up as a new group
+    assert_eq!(
+        diff(|new| new.groups[1].number = 2),
+        [
+            ChangeKind::Breaking,
+            ChangeKind::Breaking,
+            ChangeKind::Breaking,
+            ChangeKind::Breaking,
+            ChangeKind::Additive
+        ]
+    );
+}
+
+#[test]
+fn new_and_removed_commands() {
+    assert_eq!(
+        diff(|new| {
+            let mut command = new.groups[1].commands[3].clone();
+            command.name = "Heartbeat".to_owned();
+            command.number = 5;
+            new.groups[1].commands.push(command);
+        }),
+        [ChangeKind::Additive]
+    );
+    assert_eq!(
+        diff(|new| {
+            new.groups[1].commands.pop();
+        }),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn widening_direction_to_both_is_additive() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[3].direction = Direction::Both),
+        [ChangeKind::Additive]
+    );
+}
+
+#[test]
+fn narrowing_or_flipping_direction_is_breaking() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[3].direction = Direction::HostToDevice),
+        [ChangeKind::Breaking]
+    );
+    let mut both = base();
+    both.groups[1].commands[3].direction = Direction::Both;
+    let changes = diff_protocols(&both, &base());
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::Breaking);
+}
+
+#[test]
+fn changed_reply_is_breaking() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[0].acknowledged = false),
+        [ChangeKind::Breaking]
+    );
+    assert_eq!(
+        diff(|new| new.groups[1].commands[3].associated_request = Some("SetMode".to_owned())),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn added_parameter_is_breaking() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[3]
+            .parameters
+            .push(parameter("Uptime", "u32"))),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn removed_parameter_is_breaking() {
+    assert_eq!(
+        diff(|new| {
+            new.groups[1].commands[0].parameters.pop();
+        }),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn retyped_parameter_is_breaking() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[0].parameters[1].param_type = "u16".to_owned()),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn rescaled_parameter_is_breaking() {
+    assert_eq!(
+        diff(|new| {
+            let level = &mut new.groups[1].commands[0].parameters[1];
+            level.unit = Some("percent".to_owned());
+            level.scale = Some(0.5);
+        }),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn renamed_or_rebounded_parameter_is_compatible() {
+    assert_eq!(
+        diff(|new| new.groups[1].commands[0].parameters[1].name = "Brightness".to_owned()),
+        [ChangeKind::Compatible]
+    );
+    assert_eq!(
+        diff(|new| new.groups[1].commands[0].parameters[1].max = Some(100.0)),
+        [ChangeKind::Compatible]
+    );
+}
+
+#[test]
+fn new_type_value_is_breaking() {
+    // Older peers reject the value, so messages which carry it are dropped
+    assert_eq!(
+        diff(|new| new.types[0].values.push(TypeValue {
+            name: "Auto".to_owned(),
+            value: 2,
+            description: String::new(),
+        })),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn removed_or_renumbered_type_value_is_breaking() {
+    assert_eq!(
+        diff(|new| {
+            new.types[0].values.pop();
+        }),
+        [ChangeKind::Breaking]
+    );
+    assert_eq!(
+        diff(|new| new.types[0].values[1].value = 2),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn changed_type_repr_is_breaking() {
+    assert_eq!(
+        diff(|new| new.types[0].repr = "u16".to_owned()),
+        [ChangeKind::Breaking]
+    );
+}
+
+#[test]
+fn version_bump_matches_changes() {
+    let bumped = |version: &str, change: fn(&mut ProtocolFile)| {
+        let mut new = base();
+        change(&mut new);
+        new.version = version.to_owned();
+        check_version_bump(&base(), &new, &diff_protocols(&base(), &new))
+    };
+    let remove_status = |new: &mut ProtocolFile| {
+        new.groups[1].commands.pop();
+    };
+    let widen_status =
+        |new: &mut ProtocolFile| new.groups[1].commands[3].direction = Direction::Both;
+
+    assert!(bumped("1.0.0", |_| {}).is_ok());
+    assert!(bumped("0.9.0", |_| {}).is_err());
+    assert!(bumped("1.0.0", widen_status).is_err());
+    assert!(bumped("1.0.1", widen_status).is_ok());
+    assert!(bumped("1.1.0", remove_status).is_err());
+    assert!(bumped("2.0.0", remove_status).is_ok());
+}
+
+#[test]
+fn old_schema_revisions_can_be_diffed() {
+    // Written before messages had a direction, and without the GenericResponse which its
+    // acknowledged message needs now, so it doesn't pass check_protocol
+    let mut old = serde_json::to_value(base()).unwrap();
+    old["groups"].as_array_mut().unwrap().remove(0);
+    for command in old["groups"][0]["commands"].as_array_mut().unwrap() {
+        command.as_object_mut().unwrap().remove("direction");
+    }
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("old-schema.json5");
+    std::fs::write(&path, old.to_string()).unwrap();
+    let old = load_old_protocol(&path).unwrap();
+
+    assert!(old.groups[0]
+        .commands
+        .iter()
+        .all(|command| command.direction == Direction::Both));
+    // Every message went either way before, and the group with GenericResponse is new
+    let kinds: Vec<_> = diff_protocols(&old, &base())
+        .into_iter()
+        .map(|change| change.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ChangeKind::Breaking,
+            ChangeKind::Breaking,
+            ChangeKind::Breaking,
+            ChangeKind::Breaking,
+            ChangeKind::Additive,
+            ChangeKind::Additive
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+//! Compares the generator's output for `tests/golden/protocol.json5` against the expected files
+//! next to it. After an intended change to the generator, regenerate them from
+//! `atmosensor-host-apps` with e.g.
+//!
+//! ```text
+//! cargo run --bin protocol-generator -- dissector -p atmosensor-codegen/tests/golden/protocol.json5 -o atmosensor-codegen/tests/golden/dissector.lua
+//! cargo run --bin protocol-generator -- render -p atmosensor-codegen/tests/golden/protocol.json5 --templates atmosensor-codegen/tests/golden/templates atmosensor.h.j2=atmosensor-codegen/tests/golden/atmosensor.h
+//! ```
+
+use atmosensor_codegen::ProtocolFile;
+use std::path::{Path, PathBuf};
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn golden_protocol() -> ProtocolFile {
+    atmosensor_codegen::load_protocol(&golden_dir().join("protocol.json5")).unwrap()
+}
+
+fn assert_matches_golden(generated: &str, golden_name: &str) {
+    let expected = std::fs::read_to_string(golden_dir().join(golden_name)).unwrap();
+    let mismatch = generated
+        .lines()
+        .zip(expected.lines())
+        .enumerate()
+        .find(|(_, (generated, expected))| generated != expected);
+    if let Some((line, (generated, expected))) = mismatch {
+        panic!(
+            "{golden_name}:{} differs\n  expected: {expected}\n  generated: {generated}",
+            line + 1
+        );
+    }
+    assert_eq!(
+        generated.lines().count(),
+        expected.lines().count(),
+        "{golden_name} has a different number of lines"
+    );
+}
+
+#[test]
+fn dissector_matches_golden_file() {
+    let generated = atmosensor_codegen::generate_dissector(&golden_protocol()).unwrap();
+    assert_matches_golden(&generated, "dissector.lua");
+}
+
+#[test]
+fn custom_template_matches_golden_file() {
+    let generated = atmosensor_codegen::render_templates(
+        &golden_protocol(),
+        &golden_dir().join("templates"),
+        &["atmosensor.h.j2".to_owned()],
+    )
+    .unwrap();
+    assert_matches_golden(&generated[0], "atmosensor.h");
+}
@@ -0,0 +1,61 @@
+/* Generated by protocol-generator from protocol.json5 version 1.2.3, do not edit */
+#ifndef ATMOSENSOR_PROTOCOL_H
+#define ATMOSENSOR_PROTOCOL_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define ATMOSENSOR_PROTOCOL_VERSION "1.2.3"
+#define ATMOSENSOR_PROTOCOL_HASH 0x27e6447bu
+
+/* Which sensor a value came from */
+typedef uint8_t SensorKind;
+#define SENSOR_KIND_SCD_30 0
+#define SENSOR_KIND_INTERNAL 1
+
+/* Events which the device reports */
+typedef uint16_t Events;
+#define EVENTS_NEW_DATA 1
+#define EVENTS_LOG_LINE 256
+
+/* Sensor */
+#define ATMOSENSOR_GROUP_SENSOR 0x01
+
+/* Sets every "option" at once */
+#define ATMOSENSOR_CONFIGURE 0x00
+typedef struct {
+    bool enabled;
+    uint16_t interval;
+    int8_t offset;
+    int16_t altitude;
+    uint32_t uptime;
+    int32_t drift;
+    float scale;
+    uint8_t serial[6];
+    uint8_t label_len;
+    char label[16];
+    uint8_t blob_len;
+    uint8_t blob[8];
+    SensorKind kind;
+    Events subscribed;
+} Configure;
+
+/* Requests the latest reading */
+#define ATMOSENSOR_READ_LEVEL 0x01
+
+/* The latest reading */
+#define ATMOSENSOR_READ_LEVEL_RESPONSE 0x02
+typedef struct {
+    uint8_t level;
+} ReadLevelResponse;
+
+/* Utility */
+#define ATMOSENSOR_GROUP_UTILITY 0xaa
+
+/* Acknowledges a request */
+#define ATMOSENSOR_GENERIC_RESPONSE 0x00
+typedef struct {
+    bool successful;
+} GenericResponse;
+
+#endif
\ No newline at end of file
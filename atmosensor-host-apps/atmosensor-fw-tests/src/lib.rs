@@ -0,0 +1,11 @@
+//! The firmware's target-independent code, compiled for the host so it can be tested there.
+//! atmosensor-fw itself only builds for its microcontroller, so its sources are included
+//! directly rather than depended upon. They're left to the firmware's own lints.
+
+#[allow(clippy::all)]
+#[path = "../../../atmosensor-fw/src/protocol/mod.rs"]
+pub mod protocol;
+
+#[allow(clippy::all)]
+#[path = "../../../atmosensor-fw/src/utils/cobs_buffer.rs"]
+pub mod cobs_buffer;
@@ -0,0 +1,63 @@
+//! Checks that every message from the host reaches the firmware's handler, including the ones
+//! it doesn't know, so that the host is told rather than left waiting for a timeout.
+
+use atmosensor_fw_tests::protocol::{dispatch, Handler, HostMessage, SetAltitude};
+
+/// Handles only `SetAltitude` and records what it doesn't support
+#[derive(Default)]
+struct Recorder {
+    altitudes: Vec<u16>,
+    not_supported: Vec<(u8, u8)>,
+}
+
+impl Handler for Recorder {
+    fn not_supported(&mut self, group: u8, number: u8) {
+        self.not_supported.push((group, number));
+    }
+
+    fn set_altitude(&mut self, msg: SetAltitude) {
+        self.altitudes.push(msg.altitude);
+    }
+}
+
+fn handle(raw: &[u8]) -> Recorder {
+    let mut recorder = Recorder::default();
+    dispatch(&mut recorder, HostMessage::decode(raw).unwrap());
+    recorder
+}
+
+#[test]
+fn implemented_messages_are_handled() {
+    let recorder = handle(&[0x01, 0x01, 0x01, 0xa4]);
+    assert_eq!(recorder.altitudes, [420]);
+    assert!(recorder.not_supported.is_empty());
+}
+
+#[test]
+fn unimplemented_messages_are_not_supported() {
+    let recorder = handle(&[0xde, 0x00]);
+    assert_eq!(recorder.not_supported, [(0xde, 0x00)]);
+}
+
+#[test]
+fn unknown_messages_are_not_supported() {
+    let recorder = handle(&[0x42, 0x07, 0x01, 0x02, 0x03]);
+    assert!(matches!(
+        HostMessage::decode(&[0x42, 0x07]),
+        Some(HostMessage::Unknown {
+            group: 0x42,
+            number: 0x07
+        })
+    ));
+    assert_eq!(recorder.not_supported, [(0x42, 0x07)]);
+}
+
+#[test]
+fn malformed_messages_are_dropped() {
+    // Too short for its parameters, or for a header
+    assert!(HostMessage::decode(&[0x01, 0x01]).is_none());
+    assert!(HostMessage::decode(&[0x01]).is_none());
+    // Longer than its parameters, as the client rejects it too
+    assert!(HostMessage::decode(&[0x01, 0x01, 0x01, 0xa4, 0x00]).is_none());
+    assert!(HostMessage::decode(&[0xde, 0x00, 0x00]).is_none());
+}
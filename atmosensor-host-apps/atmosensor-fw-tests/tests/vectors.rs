@@ -0,0 +1,146 @@
+//! Checks the firmware's protocol code, the frames it sends and its receive buffer against the
+//! golden test vectors in `usb-protocol/vectors.json5`, which atmosensor-client is tested
+//! against too. Both sides are compared by encoded bytes: the firmware has to decode each
+//! vector and encode it again, and the client has to build it from the vector's args.
+
+use atmosensor_client::protocol as client;
+use atmosensor_codegen::{load_vectors, TestVector};
+use atmosensor_fw_tests::cobs_buffer::CobsBuffer;
+use atmosensor_fw_tests::protocol::{crc16, encode_frame, strip_crc, DeviceMessage, HostMessage};
+use std::path::Path;
+
+fn vectors() -> Vec<TestVector> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../usb-protocol/vectors.json5");
+    load_vectors(&path).unwrap()
+}
+
+fn context(vector: &TestVector) -> String {
+    format!("{} {:?}", vector.message, vector.args)
+}
+
+/// The vector's message as the client builds it from its args, encoded
+fn client_message(vector: &TestVector) -> Vec<u8> {
+    let args: Vec<_> = vector.args.iter().map(String::as_str).collect();
+    let encoded = match client::HostMessage::from_args(&vector.message, &args) {
+        Ok(msg) => msg.to_bytes(),
+        Err(client::ArgsError::UnknownMessage(_)) => {
+            let msg = client::DeviceMessage::from_args(&vector.message, &args);
+            msg.unwrap_or_else(|err| panic!("{}: {err}", context(vector)))
+                .to_bytes()
+        }
+        Err(err) => panic!("{}: {err}", context(vector)),
+    };
+    encoded.unwrap_or_else(|err| panic!("{}: {err}", context(vector)))
+}
+
+/// Decodes the vector's raw bytes as the firmware does, and encodes the result again
+fn decode_and_encode(raw: &[u8]) -> Option<Vec<u8>> {
+    let mut buffer = [0; 128];
+    let bytes_encoded = match HostMessage::decode(raw)? {
+        HostMessage::Unknown { .. } => DeviceMessage::decode(raw)?.encode_into(&mut buffer),
+        msg => msg.encode_into(&mut buffer),
+    };
+    Some(buffer[..bytes_encoded.ok()?].to_vec())
+}
+
+/// The frame of a vector before COBS, as `CobsBuffer::read_packet` should return it
+fn decoded_frame(vector: &TestVector) -> Vec<u8> {
+    let mut frame = vec![vector.sequence];
+    frame.extend_from_slice(&vector.raw);
+    frame.extend_from_slice(&crc16(&frame).to_be_bytes());
+    frame
+}
+
+#[test]
+fn messages_match_vectors() {
+    for vector in vectors() {
+        let context = context(&vector);
+        let encoded = decode_and_encode(&vector.raw).expect(&context);
+        assert_eq!(encoded, vector.raw, "{context}");
+        assert_eq!(client_message(&vector), vector.raw, "{context}");
+    }
+}
+
+#[test]
+fn sent_frames_match_vectors() {
+    // The firmware only sends the device's messages
+    let vectors = vectors();
+    let sent = vectors.iter().filter_map(|vector| {
+        match (
+            HostMessage::decode(&vector.raw),
+            DeviceMessage::decode(&vector.raw),
+        ) {
+            (Some(HostMessage::Unknown { .. }), Some(msg)) => Some((vector, msg)),
+            _ => None,
+        }
+    });
+    let mut count = 0;
+    for (vector, msg) in sent {
+        let mut buffer = [0; 256];
+        let mut out = [0; 512];
+        let frame_bytes = encode_frame(vector.sequence, &msg, &mut buffer, &mut out);
+        assert_eq!(
+            frame_bytes.map(|frame_bytes| &out[..frame_bytes]),
+            Ok(&vector.frame[..]),
+            "{}",
+            context(vector)
+        );
+        count += 1;
+    }
+    assert!(count > 0, "no vector of a message which the device sends");
+}
+
+#[test]
+fn cobs_buffer_reads_vector_frames() {
+    let vectors = vectors();
+    let mut memory = [0; 1024];
+    let mut buffer = CobsBuffer::new(&mut memory);
+    // The frames arrive back to back, as the USB handler writes them into the buffer
+    critical_section::with(|cs| {
+        for vector in &vectors {
+            assert_eq!(buffer.write_bytes(&cs, &vector.frame), vector.frame.len());
+        }
+    });
+    for vector in &vectors {
+        let mut packet = [0; 128];
+        let read = critical_section::with(|cs| buffer.read_packet(&cs, &mut packet));
+        let Ok(bytes_read) = read else {
+            panic!("{}: no packet", context(vector));
+        };
+        assert_eq!(
+            packet[..bytes_read],
+            decoded_frame(vector),
+            "{}",
+            context(vector)
+        );
+        assert_eq!(
+            strip_crc(&packet[..bytes_read]),
+            Some(&decoded_frame(vector)[..bytes_read - 2]),
+            "{}",
+            context(vector)
+        );
+    }
+}
+
+#[test]
+fn cobs_buffer_reads_vector_frames_across_its_end() {
+    let mut memory = [0; 64];
+    let mut buffer = CobsBuffer::new(&mut memory);
+    // Enough frames of different lengths that they wrap around the buffer at many offsets
+    for vector in vectors().iter().cycle().take(100) {
+        let mut packet = [0; 128];
+        let read = critical_section::with(|cs| {
+            buffer.write_bytes(&cs, &vector.frame);
+            buffer.read_packet(&cs, &mut packet)
+        });
+        let Ok(bytes_read) = read else {
+            panic!("{}: no packet", context(vector));
+        };
+        assert_eq!(
+            packet[..bytes_read],
+            decoded_frame(vector),
+            "{}",
+            context(vector)
+        );
+    }
+}
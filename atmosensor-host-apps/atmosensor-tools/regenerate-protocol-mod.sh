@@ -1,4 +1,10 @@
 #!/usr/bin/env bash
+#
+# Regenerates the files which are generated from the protocol and checked in. Pass --verify to
+# only check that they're up to date instead. atmosensor-client generates its module at build
+# time so it isn't included here.
+
+set -e
 
 start_dir=$(pwd)
 
@@ -15,12 +21,27 @@ do
   fi
 done
 
-echo "Generating Rust module for protocol..."
+verify=$1
+
+echo "Generating Rust module for the firmware..."
 
 cd atmosensor-host-apps
 cargo run --bin protocol-generator -- \
+-t firmware $verify \
+-p ../usb-protocol/protocol.json5 \
+-o ../atmosensor-fw/src/protocol/autogen.rs
+
+echo "Generating protocol reference..."
+
+cargo run --bin protocol-generator -- docs $verify \
+-p ../usb-protocol/protocol.json5 \
+-o ../usb-protocol/protocol.md
+
+echo "Generating Wireshark dissector..."
+
+cargo run --bin protocol-generator -- dissector $verify \
 -p ../usb-protocol/protocol.json5 \
--o atmosensor-client/src/protocol/autogen.rs
+-o ../usb-protocol/atmosensor.lua
 
 echo "Done"
 
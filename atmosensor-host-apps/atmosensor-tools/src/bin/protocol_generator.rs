@@ -1,114 +1,216 @@
-#![allow(unused)]
-
-use clap::Parser;
-use convert_case::{Case, Casing};
-use minijinja::context;
-use serde::{Deserialize, Serialize};
-use std::{io::Write, path::PathBuf, process::Stdio};
-
-const MESSAGE_STRUCT_TMPL: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/assets/message_struct.rs.j2"
-));
-const MODULE_TMPL: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/module.rs.j2"));
+use atmosensor_codegen::ProtocolFile;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 
+/// Generates code and documentation from the USB protocol definition. Without a subcommand
+/// this generates the Rust module for one of the protocol's consumers.
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    #[arg(short = 'p')]
-    protocol_file: PathBuf,
-    #[arg(short = 'o')]
-    output_file: PathBuf,
+    #[command(subcommand)]
+    mode: Option<Mode>,
+    #[arg(short = 'p', required = true)]
+    protocol_file: Option<PathBuf>,
+    #[arg(short = 'o', required_unless_present = "check")]
+    output_file: Option<PathBuf>,
+    /// Only check the protocol file for mistakes, without generating anything
+    #[arg(long)]
+    check: bool,
+    /// Fail if the output file differs from what would be generated, instead of writing it
+    #[arg(long, conflicts_with = "check")]
+    verify: bool,
+    /// Which consumer of the protocol to generate the module for
+    #[arg(short = 't', long, value_enum, default_value_t = Target::Client)]
+    target: Target,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct ProtocolFile {
-    version: String,
-    groups: Vec<CommandGroup>,
+#[derive(Subcommand)]
+enum Mode {
+    /// Render the protocol into Markdown reference documentation
+    Docs {
+        #[arg(short = 'p')]
+        protocol_file: PathBuf,
+        #[arg(short = 'o')]
+        output_file: PathBuf,
+        /// Also render the documentation as a standalone HTML page
+        #[arg(long)]
+        html: Option<PathBuf>,
+        /// Fail if the output files differ from what would be generated, instead of writing them
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Render the protocol into a Wireshark Lua dissector
+    Dissector {
+        #[arg(short = 'p')]
+        protocol_file: PathBuf,
+        #[arg(short = 'o')]
+        output_file: PathBuf,
+        /// Fail if the output file differs from what would be generated, instead of writing it
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Render templates of your own, e.g. to generate code for another language. They get the
+    /// same filters as the built-in templates, see `atmosensor-codegen/tests/golden/templates`
+    /// for an example.
+    Render {
+        #[arg(short = 'p')]
+        protocol_file: PathBuf,
+        /// Directory which the templates are loaded from, they can include each other by their
+        /// path relative to it
+        #[arg(long)]
+        templates: PathBuf,
+        /// What to render as `TEMPLATE=OUTPUT`, with the template's path relative to
+        /// `--templates`
+        #[arg(required = true, value_parser = parse_render_target)]
+        targets: Vec<(String, PathBuf)>,
+        /// Fail if the output files differ from what would be generated, instead of writing them
+        #[arg(long)]
+        verify: bool,
+    },
+    /// List the changes between two revisions of the protocol and check that its version was
+    /// bumped accordingly
+    Diff {
+        old_protocol_file: PathBuf,
+        new_protocol_file: PathBuf,
+    },
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct CommandGroup {
-    group: String,
-    number: u8,
-    commands: Vec<Command>,
+#[derive(Clone, Copy, ValueEnum)]
+enum Target {
+    /// std module for atmosensor-client, messages are encoded into a `Vec<u8>`
+    Client,
+    /// no_std, allocation-free module for atmosensor-fw
+    Firmware,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct Command {
-    name: String,
-    associated_request: Option<String>,
-    number: u8,
-    description: String,
-    parameters: Vec<Parameter>,
+impl From<Target> for atmosensor_codegen::Target {
+    fn from(target: Target) -> Self {
+        match target {
+            Target::Client => atmosensor_codegen::Target::Client,
+            Target::Firmware => atmosensor_codegen::Target::Firmware,
+        }
+    }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct Parameter {
-    name: String,
-    #[serde(rename = "type")]
-    param_type: String,
-    description: String,
+fn parse_render_target(arg: &str) -> Result<(String, PathBuf), String> {
+    let (template, output) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected TEMPLATE=OUTPUT, found `{arg}`"))?;
+    Ok((template.to_owned(), PathBuf::from(output)))
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-
-    let protocol = std::fs::read_to_string(args.protocol_file)?;
-    let protocol = json5::from_str::<ProtocolFile>(&protocol)?;
-
-    let mut env = minijinja::Environment::new();
-    env.add_template("message", MESSAGE_STRUCT_TMPL);
-    env.add_template("module", MODULE_TMPL);
-    env.add_filter("param_case", |v: minijinja::value::Value| {
-        let value = serde_json::to_value(v).unwrap();
-        let parameter_name = value.as_str().unwrap().to_case(Case::Snake);
-        minijinja::value::Value::from_serializable(&parameter_name)
-    });
-    let tmpl = env.get_template("message").unwrap();
-
-    let command_definitions = protocol
-        .groups
-        .iter()
-        .flat_map(|grp| {
-            grp.commands.iter().map(|cmd| {
-                tmpl.render(context! { group => grp.number, command => cmd })
-                    .unwrap()
-            })
-        })
-        .collect::<Vec<_>>();
-
-    let tmpl = env.get_template("module").unwrap();
-    let module_definition = tmpl
-        .render(context! { protocol => protocol, commands => command_definitions })
-        .unwrap();
-    let module_definition = format_rust_source(&module_definition);
-
-    std::fs::write(&args.output_file, module_definition.as_bytes())?;
+fn load_protocol(path: &Path) -> Result<ProtocolFile, Box<dyn std::error::Error>> {
+    atmosensor_codegen::load_protocol(path).map_err(|err| {
+        if let atmosensor_codegen::Error::Invalid(problems) = &err {
+            for problem in problems {
+                eprintln!("{}: {problem}", path.display());
+            }
+        }
+        err.into()
+    })
+}
 
+/// Writes `contents` to `path`, or with `verify` only checks that `path` already holds them
+fn write_output(
+    path: &Path,
+    contents: &str,
+    verify: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !verify {
+        std::fs::write(path, contents.as_bytes())?;
+        return Ok(());
+    }
+    let existing = std::fs::read_to_string(path)
+        .map_err(|err| format!("could not read {}: {err}", path.display()))?;
+    if existing != contents {
+        return Err(format!(
+            "{} is out of date with the protocol, regenerate it with atmosensor-tools/regenerate-protocol-mod.sh",
+            path.display()
+        )
+        .into());
+    }
     Ok(())
 }
 
-fn format_rust_source(source: &str) -> std::borrow::Cow<'_, str> {
-    if let Ok(mut process) = std::process::Command::new("rustfmt")
-        .arg("--emit=stdout")
-        .arg("--edition=2021")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .spawn()
-    {
-        {
-            let stdin = process.stdin.as_mut().unwrap();
-            stdin.write_all(source.as_bytes()).unwrap()
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    match args.mode {
+        Some(Mode::Docs {
+            protocol_file,
+            output_file,
+            html,
+            verify,
+        }) => {
+            let protocol = load_protocol(&protocol_file)?;
+            let docs = atmosensor_codegen::generate_docs(&protocol)?;
+            write_output(&output_file, &docs, verify)?;
+            if let Some(html_file) = html {
+                let html = atmosensor_codegen::markdown_to_html("Atmosensor USB protocol", &docs);
+                write_output(&html_file, &html, verify)?;
+            }
+            return Ok(());
+        }
+        Some(Mode::Dissector {
+            protocol_file,
+            output_file,
+            verify,
+        }) => {
+            let protocol = load_protocol(&protocol_file)?;
+            let dissector = atmosensor_codegen::generate_dissector(&protocol)?;
+            write_output(&output_file, &dissector, verify)?;
+            return Ok(());
+        }
+        Some(Mode::Render {
+            protocol_file,
+            templates,
+            targets,
+            verify,
+        }) => {
+            let protocol = load_protocol(&protocol_file)?;
+            let (names, outputs): (Vec<_>, Vec<_>) = targets.into_iter().unzip();
+            let rendered = atmosensor_codegen::render_templates(&protocol, &templates, &names)?;
+            for (output_file, contents) in outputs.iter().zip(&rendered) {
+                write_output(output_file, contents, verify)?;
+            }
+            return Ok(());
         }
-        if let Ok(output) = process.wait_with_output() {
-            if output.status.success() {
-                return std::str::from_utf8(&output.stdout[..])
-                    .unwrap()
-                    .to_owned()
-                    .into();
+        Some(Mode::Diff {
+            old_protocol_file,
+            new_protocol_file,
+        }) => {
+            // Only the new revision has to follow the current schema and pass every check
+            let old = atmosensor_codegen::load_old_protocol(&old_protocol_file)?;
+            let new = load_protocol(&new_protocol_file)?;
+            let changes = atmosensor_codegen::diff_protocols(&old, &new);
+            if changes.is_empty() {
+                println!("No changes");
             }
+            for change in &changes {
+                println!("{}: {}", change.kind, change.description);
+            }
+            atmosensor_codegen::check_version_bump(&old, &new, &changes)?;
+            return Ok(());
         }
+        None => {}
+    }
+
+    let protocol_file = args.protocol_file.expect("required without a subcommand");
+    let protocol = load_protocol(&protocol_file)?;
+    if args.check {
+        return Ok(());
     }
-    std::borrow::Cow::Borrowed(source)
+
+    let module_definition = atmosensor_codegen::generate_module(&protocol, args.target.into())?;
+    let module_definition = atmosensor_codegen::format_rust_source(&module_definition)?;
+
+    let output_file = args.output_file.expect("required unless checking");
+    write_output(&output_file, &module_definition, args.verify)?;
+
+    Ok(())
 }
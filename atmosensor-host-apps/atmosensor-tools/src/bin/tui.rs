@@ -1,3 +1,4 @@
+use atmosensor_client::protocol::{DeviceMessage, HostMessage, LogLevel};
 use atmosensor_client::Atmosensor;
 use atmosensor_tools::{bytes_to_hex_str, hex_str_to_bytes, is_hex_char};
 use crossterm::{
@@ -5,7 +6,7 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::collections::VecDeque;
-use std::string::ToString;
+use std::fmt;
 use std::sync::{Arc, Mutex};
 use tui::{
     backend::{Backend, CrosstermBackend},
@@ -18,17 +19,42 @@ use unicode_width::UnicodeWidthStr;
 
 #[derive(Clone)]
 enum Message {
-    Sent { data: Vec<u8> },
-    Received { data: Vec<u8> },
-    Error { inner: String },
+    Sent {
+        data: Vec<u8>,
+    },
+    Received {
+        data: Vec<u8>,
+    },
+    /// Log output of the firmware, shown as text rather than bytes
+    Log {
+        level: LogLevel,
+        text: String,
+    },
+    Error {
+        inner: String,
+    },
 }
 
-impl ToString for Message {
-    fn to_string(&self) -> String {
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Message::Sent { data } => format!("tx {}", bytes_to_hex_str(&data[..])),
-            Message::Received { data } => format!("rx {}", bytes_to_hex_str(&data[..])),
-            Message::Error { inner } => format!("err {}", inner),
+            Message::Sent { data } => write!(f, "tx {}", bytes_to_hex_str(&data[..])),
+            Message::Received { data } => {
+                write!(f, "rx {}", bytes_to_hex_str(&data[..]))?;
+                // Known messages are also shown field by field
+                match DeviceMessage::from_bytes(data) {
+                    Ok(DeviceMessage::Unknown { .. }) | Err(_) => Ok(()),
+                    Ok(msg) => {
+                        write!(f, "  {}", msg.name())?;
+                        for (name, value) in msg.field_values() {
+                            write!(f, " {name}={value}")?;
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            Message::Log { level, text } => write!(f, "log {level:?} {text}"),
+            Message::Error { inner } => write!(f, "err {}", inner),
         }
     }
 }
@@ -42,22 +68,43 @@ struct ApplicationState {
 impl ApplicationState {
     fn push_cmd(&mut self) {
         let input: Vec<_> = self.input.drain(..).collect();
-        if !input.is_empty() {
-            if input.len() % 2 == 0 {
-                let byte_data = hex_str_to_bytes(&input[..]).unwrap();
-                let mut msgs = self.messages.lock().unwrap();
-                msgs.push_back(Message::Sent {
-                    data: byte_data.clone(),
-                });
-                // If there's an error here the channel closed so just exit
-                let _ = self.io_handle.blocking_send(byte_data);
-            } else {
-                let mut msgs = self.messages.lock().unwrap();
-                msgs.push_back(Message::Error {
-                    inner: format!("odd number of chars in hex string, found {}", input.len()),
-                });
-            }
+        if input.is_empty() {
+            return;
         }
+        let byte_data = if input.iter().all(|ch| is_hex_char(*ch)) {
+            match hex_str_to_bytes(&input[..]) {
+                Some(byte_data) => byte_data,
+                None => {
+                    self.push_error(format!(
+                        "odd number of chars in hex string, found {}",
+                        input.len()
+                    ));
+                    return;
+                }
+            }
+        } else {
+            // Anything but raw hex is a message name followed by its arguments
+            let input: String = input.into_iter().collect();
+            let mut words = input.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let args: Vec<_> = words.collect();
+            match HostMessage::from_args(name, &args).map(HostMessage::to_bytes) {
+                Ok(Ok(byte_data)) => byte_data,
+                Ok(Err(err)) => return self.push_error(err.to_string()),
+                Err(err) => return self.push_error(err.to_string()),
+            }
+        };
+        let mut msgs = self.messages.lock().unwrap();
+        msgs.push_back(Message::Sent {
+            data: byte_data.clone(),
+        });
+        // If there's an error here the channel closed so just exit
+        let _ = self.io_handle.blocking_send(byte_data);
+    }
+
+    fn push_error(&mut self, inner: String) {
+        let mut msgs = self.messages.lock().unwrap();
+        msgs.push_back(Message::Error { inner });
     }
 }
 
@@ -125,11 +172,9 @@ fn run_ui_context<B: Backend>(
                     app_state.push_cmd();
                     app_state.input = String::new();
                 }
+                // Either raw hex or a message name and its arguments, see `push_cmd`
                 KeyCode::Char(ch) => {
-                    if is_hex_char(ch) {
-                        // Only accept characters which are valid in hexadecimal
-                        app_state.input.push(ch);
-                    }
+                    app_state.input.push(ch);
                 }
                 KeyCode::Backspace => {
                     app_state.input.pop();
@@ -165,8 +210,15 @@ async fn io_receive(
 ) {
     loop {
         let data = reader.receive_raw().await;
+        let msg = match DeviceMessage::from_bytes(&data) {
+            Ok(DeviceMessage::LogMessage(log)) => Message::Log {
+                level: log.level,
+                text: log.text,
+            },
+            _ => Message::Received { data },
+        };
         let mut msg_queue = messages.lock().unwrap();
-        msg_queue.push_back(Message::Received { data });
+        msg_queue.push_back(msg);
     }
 }
 
@@ -1,8 +1,9 @@
-use atmosensor::protocol::{
-    Command, DisableTestLed, EnableTestLed, LastCO2DataResponse, LastHumidityResponse,
-    LastTemperatureResponse, SetAltitude,
+use atmosensor_client::protocol::{
+    DeviceMessage, DisableTestLed, EnableTestLed, LastCO2DataResponse, MeasurementReport,
+    RequestLastCO2Data, RequestLastHumidity, RequestLastTemperature, SetAltitude,
+    SetMeasurementPush, StartContinuousMeasurement,
 };
-use atmosensor_client::{self as atmosensor, Atmosensor};
+use atmosensor_client::{Atmosensor, DeviceError, RequestError};
 use chrono::Utc;
 use futures::prelude::*;
 use influxdb2_derive::WriteDataPoint;
@@ -24,13 +25,15 @@ struct CO2Data {
     time: i64,
 }
 
+// The scaled values get their own fields: the older `value` fields hold whole degrees and
+// percent as integers, and InfluxDB rejects writing another type to an existing field
 #[derive(WriteDataPoint)]
 #[measurement = "temperature_c"]
 struct Temperature {
     #[influxdb(tag)]
     location: String,
     #[influxdb(field)]
-    value: i64,
+    celsius: f64,
     #[influxdb(timestamp)]
     time: i64,
 }
@@ -41,7 +44,7 @@ struct RelativeHumidity {
     #[influxdb(tag)]
     location: String,
     #[influxdb(field)]
-    value: u64,
+    percent: f64,
     #[influxdb(timestamp)]
     time: i64,
 }
@@ -60,107 +63,85 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .unwrap();
 
     log::info!("Connecting to Atmosensor with config: {:?}", config.device);
-    let (mut reader, mut writer) =
-        Atmosensor::new(config.device.tty_path.to_string_lossy())?.split();
+    let mut atmosensor = Atmosensor::new(config.device.tty_path.to_string_lossy())?;
+    let protocol_info = match atmosensor.handshake().await {
+        Ok(info) => {
+            log::info!(
+                "Device speaks protocol version {} (hash {:#010x})",
+                info.version,
+                info.spec_hash
+            );
+            Some(info)
+        }
+        Err(err @ RequestError::Incompatible(_)) => return Err(err.into()),
+        // Firmware from before the handshake existed doesn't answer it
+        Err(err) => {
+            log::warn!("Protocol handshake failed, assuming a compatible device: {err}");
+            None
+        }
+    };
+
+    // Pushed reports carry every value of a sample at once, otherwise each value of a new
+    // sample is requested on its own
+    let push_supported = protocol_info
+        .is_some_and(|info| info.supports(SetMeasurementPush::GROUP, SetMeasurementPush::NUMBER));
+    if push_supported {
+        match atmosensor
+            .request(SetMeasurementPush { enabled: true })
+            .await
+        {
+            Ok(_) => log::info!("Device pushes measurement reports"),
+            Err(err) => log::warn!("Failed to enable measurement reports: {err}"),
+        }
+    }
 
     log::info!("Connecting to InfluxDB with config: {:?}", config.database);
     let influx_client = config.database.make_client();
 
-    writer
-        .send(atmosensor::protocol::Command::SetAltitude(SetAltitude {
-            altitude: config.device.altitude,
-        }))
-        .await?;
-
-    writer
-        .send(atmosensor::protocol::Command::StartContinuousMeasurement(
-            atmosensor::protocol::StartContinuousMeasurement {},
-        ))
-        .await?;
+    let altitude = SetAltitude {
+        altitude: config.device.altitude,
+    };
+    if let Err(err) = atmosensor.request(altitude).await {
+        log::warn!("Failed to set the altitude: {err}");
+    }
+    atmosensor.request(StartContinuousMeasurement {}).await?;
     log::info!("Starting continuous measurement");
 
     let mut led_state = false;
 
     while running.load(Ordering::SeqCst) {
-        match reader
+        match atmosensor
             .receive_next(std::time::Duration::from_millis(500))
             .await
         {
-            Some(Command::ReportNewData(_)) => {
-                writer
-                    .send(Command::RequestLastCO2Data(
-                        atmosensor::protocol::RequestLastCO2Data {},
-                    ))
-                    .await
-                    .unwrap();
-                writer
-                    .send(Command::RequestLastTemperature(
-                        atmosensor::protocol::RequestLastTemperature {},
-                    ))
-                    .await
-                    .unwrap();
-                writer
-                    .send(Command::RequestLastHumidity(
-                        atmosensor::protocol::RequestLastHumidity {},
-                    ))
-                    .await
-                    .unwrap();
+            Some(DeviceMessage::ReportNewData(_)) => {
+                write_last_sample(&config, &influx_client, &mut atmosensor).await;
             }
-            Some(Command::LastCO2DataResponse(LastCO2DataResponse { co_2_data })) => {
-                let co2_data_points = vec![CO2Data {
-                    location: config.device.location.clone(),
-                    value: co_2_data.into(),
-                    time: Utc::now().timestamp_nanos(),
-                }];
-                if let Ok(..) = influx_client
-                    .write(&config.database.bucket, stream::iter(co2_data_points))
-                    .await
-                {
-                    log::debug!("Writing co2 data... {}", co_2_data);
-                }
+            Some(DeviceMessage::MeasurementReport(report)) => {
+                write_measurement_report(&config, &influx_client, report).await;
             }
-            Some(Command::LastTemperatureResponse(LastTemperatureResponse { temperature })) => {
-                let temp_data_points = vec![Temperature {
-                    location: config.device.location.clone(),
-                    value: temperature.into(),
-                    time: Utc::now().timestamp_nanos(),
-                }];
-                if let Ok(..) = influx_client
-                    .write(&config.database.bucket, stream::iter(temp_data_points))
-                    .await
-                {
-                    log::debug!("Writing temperature data: {}", temperature);
-                }
+            Some(DeviceMessage::LogMessage(msg)) => {
+                log::log!(
+                    target: "atmosensor_fw",
+                    msg.level.into(),
+                    "[{}] {}",
+                    config.device.location,
+                    msg.text
+                );
             }
-            Some(Command::LastHumidityResponse(LastHumidityResponse { relative_humidity })) => {
-                let humidity_data_points = vec![RelativeHumidity {
-                    location: config.device.location.clone(),
-                    value: relative_humidity.into(),
-                    time: Utc::now().timestamp_nanos(),
-                }];
-                if let Ok(..) = influx_client
-                    .write(&config.database.bucket, stream::iter(humidity_data_points))
-                    .await
-                {
-                    log::debug!("Writing relative humidity data: {}", relative_humidity);
-                }
+            Some(DeviceMessage::ErrorResponse(response)) => {
+                log::error!("{}", DeviceError::from(response));
             }
             Some(other) => {
-                log::warn!("Unhandled command: {other:?}");
+                log::warn!("Unhandled command: {other}");
             }
             None => {
                 log::debug!("Timed out");
                 led_state = !led_state;
                 if led_state {
-                    writer
-                        .send(Command::EnableTestLed(EnableTestLed {}))
-                        .await
-                        .unwrap();
+                    atmosensor.send(EnableTestLed {}).await?;
                 } else {
-                    writer
-                        .send(Command::DisableTestLed(DisableTestLed {}))
-                        .await
-                        .unwrap();
+                    atmosensor.send(DisableTestLed {}).await?;
                 }
             }
         }
@@ -168,3 +149,109 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Requests each value of the newest sample on its own, for devices which don't push reports
+async fn write_last_sample(
+    config: &atmosensord::config::Config,
+    influx_client: &influxdb2::Client,
+    atmosensor: &mut Atmosensor,
+) {
+    let location = &config.device.location;
+    let bucket = &config.database.bucket;
+
+    match atmosensor.request(RequestLastCO2Data {}).await {
+        Ok(LastCO2DataResponse { co_2_data }) => {
+            let co2_data_points = vec![CO2Data {
+                location: location.clone(),
+                value: co_2_data.into(),
+                time: Utc::now().timestamp_nanos(),
+            }];
+            if influx_client
+                .write(bucket, stream::iter(co2_data_points))
+                .await
+                .is_ok()
+            {
+                log::debug!("Writing co2 data... {}", co_2_data);
+            }
+        }
+        Err(err) => log::warn!("Failed to request the CO2 data: {err}"),
+    }
+
+    match atmosensor.request(RequestLastTemperature {}).await {
+        Ok(response) => {
+            let temperature = response.temperature_celsius();
+            let temp_data_points = vec![Temperature {
+                location: location.clone(),
+                celsius: temperature.into(),
+                time: Utc::now().timestamp_nanos(),
+            }];
+            if influx_client
+                .write(bucket, stream::iter(temp_data_points))
+                .await
+                .is_ok()
+            {
+                log::debug!("Writing temperature data: {}", temperature);
+            }
+        }
+        Err(err) => log::warn!("Failed to request the temperature: {err}"),
+    }
+
+    match atmosensor.request(RequestLastHumidity {}).await {
+        Ok(response) => {
+            let relative_humidity = response.relative_humidity_percent();
+            let humidity_data_points = vec![RelativeHumidity {
+                location: location.clone(),
+                percent: relative_humidity.into(),
+                time: Utc::now().timestamp_nanos(),
+            }];
+            if influx_client
+                .write(bucket, stream::iter(humidity_data_points))
+                .await
+                .is_ok()
+            {
+                log::debug!("Writing relative humidity data: {}", relative_humidity);
+            }
+        }
+        Err(err) => log::warn!("Failed to request the relative humidity: {err}"),
+    }
+}
+
+async fn write_measurement_report(
+    config: &atmosensord::config::Config,
+    influx_client: &influxdb2::Client,
+    report: MeasurementReport,
+) {
+    let time = Utc::now().timestamp_nanos() - i64::from(report.sample_age) * 1_000_000;
+    let location = &config.device.location;
+    let co2_data_points = vec![CO2Data {
+        location: location.clone(),
+        value: report.co_2_ppm().round() as u64,
+        time,
+    }];
+    let temp_data_points = vec![Temperature {
+        location: location.clone(),
+        celsius: report.temperature_celsius().into(),
+        time,
+    }];
+    let humidity_data_points = vec![RelativeHumidity {
+        location: location.clone(),
+        percent: report.relative_humidity_percent().into(),
+        time,
+    }];
+    let bucket = &config.database.bucket;
+    if influx_client
+        .write(bucket, stream::iter(co2_data_points))
+        .await
+        .is_ok()
+        && influx_client
+            .write(bucket, stream::iter(temp_data_points))
+            .await
+            .is_ok()
+        && influx_client
+            .write(bucket, stream::iter(humidity_data_points))
+            .await
+            .is_ok()
+    {
+        log::debug!("Writing {report}");
+    }
+}
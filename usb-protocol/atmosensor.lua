@@ -0,0 +1,477 @@
+-- Wireshark dissector for the Atmosensor USB protocol, version 0.5.3.
+--
+-- This file is generated from `protocol.json5` by `protocol-generator dissector`, edit the
+-- protocol definition instead.
+--
+-- Copy it into Wireshark's personal Lua plugins folder (see Help > About Wireshark > Folders).
+-- Bulk transfers to and from the atmosensor's VID/PID are picked up automatically, other
+-- devices can be decoded with "Decode As..." on the USB bulk payload. Frames which are split
+-- across two transfers are not reassembled.
+
+local atmosensor = Proto("atmosensor", "Atmosensor USB protocol")
+
+-- Must match the UsbVidPid which atmosensor-fw enumerates with
+local USB_VID_PID = 0x16c027dd
+local URB_BULK = 0x03
+
+local group_names = {
+    [0x01] = "Sensor",
+    [0xde] = "Debug",
+    [0xaa] = "Utility",
+}
+
+-- ErrorCode: Why the device failed to carry out a command
+local error_code_values = {
+    [0x00] = "UnsupportedCommand",
+    [0x01] = "InvalidArgument",
+    [0x02] = "NoData",
+    [0x03] = "ValueOutOfRange",
+    [0x04] = "SensorNack",
+    [0x05] = "SensorTimeout",
+    [0x06] = "SensorBusError",
+}
+
+-- LogLevel: Severity of a log message from the firmware
+local log_level_values = {
+    [0x00] = "Error",
+    [0x01] = "Warn",
+    [0x02] = "Info",
+    [0x03] = "Debug",
+}
+
+-- EventMask: Kinds of message which the device sends on its own
+local event_mask_flags = {
+    { "new_data", "NewData", 0x01 },
+    { "errors", "Errors", 0x02 },
+    { "log_lines", "LogLines", 0x04 },
+    { "threshold_crossings", "ThresholdCrossings", 0x08 },
+}
+
+-- One boolean field per bit of a bitflags parameter, shown under the parameter itself
+local function flag_fields(abbrev, bits, flags)
+    local fields = {}
+    for _, flag in ipairs(flags) do
+        table.insert(fields, ProtoField.bool(abbrev .. "." .. flag[1], flag[2], bits, nil, flag[3]))
+    end
+    return fields
+end
+
+local f_sequence = ProtoField.uint8("atmosensor.sequence", "Sequence id", base.DEC)
+local f_group = ProtoField.uint8("atmosensor.group", "Group", base.HEX, group_names)
+local f_command = ProtoField.uint8("atmosensor.command", "Command", base.HEX)
+local f_message = ProtoField.string("atmosensor.message", "Message")
+local f_length = ProtoField.uint8("atmosensor.length", "Length", base.DEC)
+local f_payload = ProtoField.bytes("atmosensor.payload", "Payload")
+local f_trailing = ProtoField.bytes("atmosensor.trailing", "Trailing bytes")
+local f_crc = ProtoField.uint16("atmosensor.crc", "CRC", base.HEX)
+
+local ef_invalid_cobs = ProtoExpert.new("atmosensor.invalid_cobs", "Invalid COBS encoding",
+    expert.group.MALFORMED, expert.severity.ERROR)
+local ef_truncated = ProtoExpert.new("atmosensor.truncated", "Message is truncated",
+    expert.group.MALFORMED, expert.severity.ERROR)
+local ef_trailing = ProtoExpert.new("atmosensor.trailing_bytes", "Unexpected bytes after the last parameter",
+    expert.group.MALFORMED, expert.severity.WARN)
+local ef_unknown = ProtoExpert.new("atmosensor.unknown", "Unknown group or command",
+    expert.group.UNDECODED, expert.severity.WARN)
+local ef_incomplete = ProtoExpert.new("atmosensor.incomplete", "Frame is not terminated in this transfer",
+    expert.group.REASSEMBLE, expert.severity.NOTE)
+local ef_bad_crc = ProtoExpert.new("atmosensor.bad_crc", "CRC does not match, the receiver drops this frame",
+    expert.group.CHECKSUM, expert.severity.ERROR)
+
+-- Parameters without a size are prefixed by their length as a u8
+local messages = {
+    [0x01] = {
+        [0x00] = {
+            name = "SetMeasurementInterval",
+            params = {
+                {
+                    field = ProtoField.uint16("atmosensor.set_measurement_interval.measurement_interval", "MeasurementInterval", base.DEC, nil, nil, "Time in seconds between measurements"),
+                    size = 2,
+                },
+            },
+        },
+        [0x01] = {
+            name = "SetAltitude",
+            params = {
+                {
+                    field = ProtoField.uint16("atmosensor.set_altitude.altitude", "Altitude", base.DEC, nil, nil, "Height in meters above sea level"),
+                    size = 2,
+                },
+            },
+        },
+        [0x02] = {
+            name = "SetTemperatureOffset",
+            params = {
+                {
+                    field = ProtoField.uint16("atmosensor.set_temperature_offset.temperature_offset", "TemperatureOffset", base.DEC, nil, nil, "Offset in one-hundredths of degrees Celsius"),
+                    size = 2,
+                },
+            },
+        },
+        [0x03] = {
+            name = "StartContinuousMeasurement",
+            params = {},
+        },
+        [0x04] = {
+            name = "ReportNewData",
+            params = {},
+        },
+        [0x05] = {
+            name = "RequestLastCO2Data",
+            params = {},
+        },
+        [0x06] = {
+            name = "LastCO2DataResponse",
+            params = {
+                {
+                    field = ProtoField.uint16("atmosensor.last_co_2_data_response.co_2_data", "CO2Data", base.DEC, nil, nil, "CO2 measurement in parts per million (ppm)"),
+                    size = 2,
+                },
+            },
+        },
+        [0x07] = {
+            name = "RequestLastTemperature",
+            params = {},
+        },
+        [0x08] = {
+            name = "LastTemperatureResponse",
+            params = {
+                {
+                    field = ProtoField.int16("atmosensor.last_temperature_response.temperature", "Temperature", base.DEC, nil, nil, "Temperature measurement in one-hundredths of degrees Celsius"),
+                    size = 2,
+                },
+            },
+        },
+        [0x09] = {
+            name = "RequestLastHumidity",
+            params = {},
+        },
+        [0x0a] = {
+            name = "LastHumidityResponse",
+            params = {
+                {
+                    field = ProtoField.uint16("atmosensor.last_humidity_response.relative_humidity", "RelativeHumidity", base.DEC, nil, nil, "Relative humidity as a percentage multiplied by 10"),
+                    size = 2,
+                },
+            },
+        },
+        [0x0b] = {
+            name = "RequestMeasurementReport",
+            params = {},
+        },
+        [0x0c] = {
+            name = "MeasurementReport",
+            params = {
+                {
+                    field = ProtoField.uint32("atmosensor.measurement_report.sample_number", "SampleNumber", base.DEC, nil, nil, "Counts the samples read since the firmware started, starting at 1"),
+                    size = 4,
+                },
+                {
+                    field = ProtoField.uint32("atmosensor.measurement_report.sample_age", "SampleAge", base.DEC, nil, nil, "Time since the sample was read from the sensor"),
+                    size = 4,
+                },
+                {
+                    field = ProtoField.float("atmosensor.measurement_report.co_2", "CO2", nil, "CO2 concentration in parts per million"),
+                    size = 4,
+                },
+                {
+                    field = ProtoField.float("atmosensor.measurement_report.temperature", "Temperature", nil, "Temperature in degrees Celsius"),
+                    size = 4,
+                },
+                {
+                    field = ProtoField.float("atmosensor.measurement_report.relative_humidity", "RelativeHumidity", nil, "Relative humidity as a percentage"),
+                    size = 4,
+                },
+            },
+        },
+        [0x0d] = {
+            name = "SetMeasurementPush",
+            params = {
+                {
+                    field = ProtoField.bool("atmosensor.set_measurement_push.enabled", "Enabled", base.NONE, nil, nil, "Push a MeasurementReport instead of sending ReportNewData"),
+                    size = 1,
+                },
+            },
+        },
+    },
+    [0xde] = {
+        [0x00] = {
+            name = "Ping",
+            params = {},
+        },
+        [0x01] = {
+            name = "PingResponse",
+            params = {},
+        },
+        [0x02] = {
+            name = "LogMessage",
+            params = {
+                {
+                    field = ProtoField.uint8("atmosensor.log_message.level", "Level", base.DEC, log_level_values, nil, "Severity of the message"),
+                    size = 1,
+                },
+                {
+                    field = ProtoField.string("atmosensor.log_message.text", "Text", base.UNICODE, "The message, cut off if it's longer than the maximum length"),
+                    encoding = ENC_UTF_8,
+                },
+            },
+        },
+    },
+    [0xaa] = {
+        [0x00] = {
+            name = "EnableTestLed",
+            params = {},
+        },
+        [0x01] = {
+            name = "DisableTestLed",
+            params = {},
+        },
+        [0x02] = {
+            name = "GenericResponse",
+            params = {
+                {
+                    field = ProtoField.bool("atmosensor.generic_response.successful", "Successful", base.NONE, nil, nil, "If the request was successful or not"),
+                    size = 1,
+                },
+            },
+        },
+        [0x03] = {
+            name = "GetProtocolInfo",
+            params = {
+                {
+                    field = ProtoField.uint8("atmosensor.get_protocol_info.group", "Group", base.DEC, nil, nil, "Group whose supported commands are reported"),
+                    size = 1,
+                },
+            },
+        },
+        [0x04] = {
+            name = "ProtocolInfoResponse",
+            params = {
+                {
+                    field = ProtoField.string("atmosensor.protocol_info_response.version", "Version", base.UNICODE, "`version` of the protocol definition"),
+                    encoding = ENC_UTF_8,
+                },
+                {
+                    field = ProtoField.uint32("atmosensor.protocol_info_response.spec_hash", "SpecHash", base.DEC, nil, nil, "Hash of the protocol definition, equal if both sides were built from the same one"),
+                    size = 4,
+                },
+                {
+                    field = ProtoField.uint8("atmosensor.protocol_info_response.group", "Group", base.DEC, nil, nil, "Group which was asked for"),
+                    size = 1,
+                },
+                {
+                    field = ProtoField.bytes("atmosensor.protocol_info_response.supported_commands", "SupportedCommands", base.NONE, "Bit `n % 8` of byte `n / 8` is set if command number `n` of the group is supported"),
+                    size = 32,
+                },
+            },
+        },
+        [0x05] = {
+            name = "ErrorResponse",
+            params = {
+                {
+                    field = ProtoField.uint8("atmosensor.error_response.code", "Code", base.DEC, error_code_values, nil, "Why the command failed"),
+                    size = 1,
+                },
+                {
+                    field = ProtoField.uint8("atmosensor.error_response.failed_group", "FailedGroup", base.DEC, nil, nil, "Group of the command which failed"),
+                    size = 1,
+                },
+                {
+                    field = ProtoField.uint8("atmosensor.error_response.failed_command", "FailedCommand", base.DEC, nil, nil, "Number of the command which failed"),
+                    size = 1,
+                },
+            },
+        },
+        [0x06] = {
+            name = "SetEventMask",
+            params = {
+                {
+                    field = ProtoField.uint16("atmosensor.set_event_mask.mask", "Mask", base.HEX, nil, nil, "Kinds of message to send"),
+                    size = 2,
+                    flags = flag_fields("atmosensor.set_event_mask.mask", 16, event_mask_flags),
+                },
+            },
+        },
+    },
+}
+
+local fields = { f_sequence, f_group, f_command, f_message, f_length, f_payload, f_trailing, f_crc }
+for _, group in pairs(messages) do
+    for _, message in pairs(group) do
+        for _, param in ipairs(message.params) do
+            table.insert(fields, param.field)
+            for _, flag in ipairs(param.flags or {}) do
+                table.insert(fields, flag)
+            end
+        end
+    end
+end
+atmosensor.fields = fields
+atmosensor.experts = { ef_invalid_cobs, ef_truncated, ef_trailing, ef_unknown, ef_incomplete, ef_bad_crc }
+
+-- Undoes the COBS encoding of a frame without its 0x00 terminator, nil if it's malformed
+local function cobs_decode(encoded)
+    local decoded = ByteArray.new()
+    local index = 0
+    while index < encoded:len() do
+        local code = encoded:get_index(index)
+        if code == 0 or index + code > encoded:len() then
+            return nil
+        end
+        if code > 1 then
+            decoded:append(encoded:subset(index + 1, code - 1))
+        end
+        index = index + code
+        if code < 0xff and index < encoded:len() then
+            decoded:append(ByteArray.new("00"))
+        end
+    end
+    return decoded
+end
+
+-- Exclusive or of two 16-bit values, spelled out since Wireshark's Lua versions disagree on
+-- bitwise operators
+local function xor16(a, b)
+    local result, bit = 0, 1
+    for _ = 1, 16 do
+        if a % 2 ~= b % 2 then
+            result = result + bit
+        end
+        a, b, bit = math.floor(a / 2), math.floor(b / 2), bit * 2
+    end
+    return result
+end
+
+-- CRC-16/CCITT-FALSE of the sequence id and message in `tvb`, which follows them in every frame
+local function crc16(tvb)
+    local crc = 0xffff
+    for index = 0, tvb:len() - 1 do
+        crc = xor16(crc, tvb(index, 1):uint() * 0x100)
+        for _ = 1, 8 do
+            if crc >= 0x8000 then
+                crc = xor16((crc - 0x8000) * 2, 0x1021)
+            else
+                crc = crc * 2
+            end
+        end
+    end
+    return crc
+end
+
+-- Adds a parameter to the tree, returning the offset after it or nil if it's truncated
+local function dissect_param(tvb, tree, param, offset)
+    local size = param.size
+    local length_offset = nil
+    if not size then
+        if tvb:len() < offset + 1 then
+            return nil
+        end
+        length_offset = offset
+        size = tvb(offset, 1):uint()
+        offset = offset + 1
+    end
+    if tvb:len() < offset + size then
+        return nil
+    end
+
+    local item = tree:add_packet_field(param.field, tvb(offset, size), param.encoding or ENC_BIG_ENDIAN)
+    if length_offset then
+        item:add(f_length, tvb(length_offset, 1))
+    end
+    for _, flag in ipairs(param.flags or {}) do
+        item:add(flag, tvb(offset, size))
+    end
+    return offset + size
+end
+
+-- Decodes one message, returning the summary for the info column
+local function dissect_message(tvb, tree)
+    local group = tvb(0, 1):uint()
+    local number = tvb(1, 1):uint()
+    tree:add(f_group, tvb(0, 1))
+    local command_item = tree:add(f_command, tvb(1, 1))
+
+    local message = messages[group] and messages[group][number]
+    if not message then
+        tree:add_proto_expert_info(ef_unknown)
+        if tvb:len() > 2 then
+            tree:add(f_payload, tvb(2))
+        end
+        return string.format("Unknown (0x%02x, 0x%02x)", group, number)
+    end
+    command_item:append_text(" (" .. message.name .. ")")
+    tree:add(f_message, tvb(0, 2), message.name):set_generated()
+    tree:append_text(", " .. message.name)
+
+    local offset = 2
+    for _, param in ipairs(message.params) do
+        offset = dissect_param(tvb, tree, param, offset)
+        if not offset then
+            tree:add_proto_expert_info(ef_truncated)
+            return message.name
+        end
+    end
+    if offset < tvb:len() then
+        tree:add(f_trailing, tvb(offset)):add_proto_expert_info(ef_trailing)
+    end
+    return message.name
+end
+
+-- Decodes the COBS frame in `range`, which excludes the 0x00 terminator
+local function dissect_frame(range, tree)
+    local decoded = cobs_decode(range:bytes())
+    if not decoded then
+        tree:add_proto_expert_info(ef_invalid_cobs)
+        return "Invalid frame"
+    end
+    -- Sequence id, group, command and CRC
+    if decoded:len() < 5 then
+        tree:add_proto_expert_info(ef_truncated)
+        return "Truncated"
+    end
+    local frame = decoded:tvb("Decoded frame")
+    tree:add(f_sequence, frame(0, 1))
+    local message = frame(1, frame:len() - 3):tvb()
+    local crc_range = frame(frame:len() - 2, 2)
+    -- A corrupted message is still dissected so it can be inspected
+    local summary = dissect_message(message, tree)
+    local crc_item = tree:add(f_crc, crc_range)
+    if crc16(frame(0, frame:len() - 2):tvb()) ~= crc_range:uint() then
+        crc_item:add_proto_expert_info(ef_bad_crc)
+        summary = summary .. " (bad CRC)"
+    end
+    return summary
+end
+
+local usb_transfer_type = Field.new("usb.transfer_type")
+
+function atmosensor.dissector(tvb, pinfo, tree)
+    local transfer_type = usb_transfer_type()
+    if tvb:len() == 0 or (transfer_type and transfer_type.value ~= URB_BULK) then
+        return 0
+    end
+    pinfo.cols.protocol = atmosensor.name
+
+    local summaries = {}
+    local start = 0
+    for index = 0, tvb:len() - 1 do
+        if tvb(index, 1):uint() == 0 then
+            if index > start then
+                local subtree = tree:add(atmosensor, tvb(start, index - start + 1))
+                table.insert(summaries, dissect_frame(tvb(start, index - start), subtree))
+            end
+            start = index + 1
+        end
+    end
+    -- Both sides terminate every frame, so anything after the last terminator is the start of
+    -- a frame which continues in the next transfer
+    if start < tvb:len() then
+        tree:add(atmosensor, tvb(start)):add_proto_expert_info(ef_incomplete)
+    end
+
+    pinfo.cols.info = table.concat(summaries, ", ")
+    return tvb:len()
+end
+
+DissectorTable.get("usb.product"):add(USB_VID_PID, atmosensor)
+DissectorTable.get("usb.bulk"):add_for_decode_as(atmosensor)
\ No newline at end of file